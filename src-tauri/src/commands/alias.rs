@@ -0,0 +1,250 @@
+use super::fnm_layout::{AliasEntry, FnmDir};
+use super::operation::{lock_current_fnm_dir, Operation, OperationKind};
+use super::runner::{run_blocking, FnmRunner, ProcessRunner};
+use super::semver::VersionSpec;
+use crate::error::FnmGuiError;
+use std::fs;
+use tauri::command;
+
+/// 别名的最大长度
+const MAX_ALIAS_LEN: usize = 64;
+
+/// 列出 fnm 目录下的全部别名，dangling 标记目标版本已被删除的别名
+#[command]
+pub async fn list_aliases() -> Result<Vec<AliasEntry>, FnmGuiError> {
+    run_blocking(|| Ok(FnmDir::current()?.aliases())).await
+}
+
+/// 创建或覆盖别名
+#[command]
+pub async fn create_alias(name: String, version: String) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::new(OperationKind::Alias, &version)).await?;
+    run_blocking(move || create(&ProcessRunner, &name, &version)).await
+}
+
+/// 删除别名
+#[command]
+pub async fn delete_alias(name: String) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::global(OperationKind::Alias)).await?;
+    run_blocking(move || delete(&ProcessRunner, &name)).await
+}
+
+/// 重命名别名
+#[command]
+pub async fn rename_alias(from: String, to: String) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::global(OperationKind::Alias)).await?;
+    run_blocking(move || rename(&ProcessRunner, &from, &to)).await
+}
+
+/// 删除所有指向已卸载版本的别名，返回被删除的别名
+#[command]
+pub async fn repair_aliases() -> Result<Vec<String>, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::global(OperationKind::Alias)).await?;
+    run_blocking(|| repair(&FnmDir::current()?)).await
+}
+
+pub fn create(runner: &dyn FnmRunner, name: &str, version: &str) -> Result<String, FnmGuiError> {
+    validate_alias_name(name)?;
+
+    // fnm 只在别名路径存在时才覆盖，失效的链接会导致创建失败，需要先删除
+    let dir = FnmDir::new(runner.fnm_dir()?);
+    if let Some(existing) = find_alias(&dir, name) {
+        if existing.dangling {
+            remove_alias_file(&dir, name)?;
+        }
+    }
+
+    let output = runner.run(&["alias", version, name])?;
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output(
+            "fnm alias",
+            Some(version),
+            &output,
+        ));
+    }
+
+    Ok(format!("已创建别名 {} -> {}", name, version))
+}
+
+pub fn delete(runner: &dyn FnmRunner, name: &str) -> Result<String, FnmGuiError> {
+    let dir = FnmDir::new(runner.fnm_dir()?);
+    let alias =
+        find_alias(&dir, name).ok_or_else(|| FnmGuiError::AliasNotFound(name.to_string()))?;
+
+    // fnm unalias 无法识别目标已不存在的别名，直接删除链接
+    if alias.dangling {
+        remove_alias_file(&dir, name)?;
+    } else {
+        let output = runner.run(&["unalias", name])?;
+        if !output.is_success() {
+            return Err(FnmGuiError::from_output("fnm unalias", None, &output));
+        }
+    }
+
+    Ok(format!("已删除别名 {}", name))
+}
+
+pub fn rename(runner: &dyn FnmRunner, from: &str, to: &str) -> Result<String, FnmGuiError> {
+    validate_alias_name(to)?;
+
+    let dir = FnmDir::new(runner.fnm_dir()?);
+    let alias =
+        find_alias(&dir, from).ok_or_else(|| FnmGuiError::AliasNotFound(from.to_string()))?;
+    if from == to {
+        return Ok(format!("别名 {} 未改变", from));
+    }
+    if find_alias(&dir, to).is_some_and(|existing| !existing.dangling) {
+        return Err(FnmGuiError::AliasExists(to.to_string()));
+    }
+
+    let version = match alias.version {
+        Some(version) if !alias.dangling => version,
+        version => {
+            return Err(FnmGuiError::VersionNotInstalled(
+                version.unwrap_or(alias.target),
+            ))
+        }
+    };
+
+    create(runner, to, &version)?;
+    // 旧别名删除失败时撤销新建的别名，避免同一版本出现两个别名
+    if let Err(e) = delete(runner, from) {
+        let _ = delete(runner, to);
+        return Err(e);
+    }
+
+    Ok(format!("已将别名 {} 重命名为 {}", from, to))
+}
+
+pub fn repair(dir: &FnmDir) -> Result<Vec<String>, FnmGuiError> {
+    let mut removed = Vec::new();
+    for alias in dir.aliases().into_iter().filter(|alias| alias.dangling) {
+        remove_alias_file(dir, &alias.name)?;
+        removed.push(alias.name);
+    }
+    Ok(removed)
+}
+
+/// 检查别名是否可以作为 fnm 别名使用
+/// 别名会成为 aliases 目录下的文件名，并且不能与版本号混淆
+pub fn validate_alias_name(name: &str) -> Result<(), FnmGuiError> {
+    let invalid = |reason: &str| {
+        Err(FnmGuiError::InvalidInput(format!(
+            "别名 \"{}\" 无效：{}",
+            name, reason
+        )))
+    };
+
+    if name.is_empty() {
+        return invalid("不能为空");
+    }
+    if name.len() > MAX_ALIAS_LEN {
+        return invalid(&format!("长度不能超过 {} 个字符", MAX_ALIAS_LEN));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return invalid("只能包含字母、数字、-、_ 和 .");
+    }
+    if name.starts_with(['.', '-']) {
+        return invalid("不能以 . 或 - 开头");
+    }
+    if name == "system" {
+        return invalid("system 为 fnm 保留名称");
+    }
+
+    let digits = name.strip_prefix('v').unwrap_or(name);
+    if digits.starts_with(|c: char| c.is_ascii_digit())
+        || !matches!(VersionSpec::parse(name), VersionSpec::Alias(_))
+    {
+        return invalid("不能与版本号混淆");
+    }
+
+    Ok(())
+}
+
+/// 按名称查找 aliases 目录中实际存在的别名
+fn find_alias(dir: &FnmDir, name: &str) -> Option<AliasEntry> {
+    dir.aliases().into_iter().find(|alias| alias.name == name)
+}
+
+/// 直接删除别名文件或链接（Windows 上的 junction 需要按目录删除）
+fn remove_alias_file(dir: &FnmDir, name: &str) -> Result<(), FnmGuiError> {
+    let path = dir.aliases_dir().join(name);
+    fs::remove_file(&path)
+        .or_else(|_| fs::remove_dir(&path))
+        .map_err(|e| FnmGuiError::io(format!("删除别名 {} 失败", name), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[cfg(unix)]
+    fn link(target: &Path, link: &Path) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    #[cfg(windows)]
+    fn link(target: &Path, link: &Path) {
+        std::os::windows::fs::symlink_dir(target, link).unwrap();
+    }
+
+    #[test]
+    fn validates_alias_names() {
+        let longest = "a".repeat(MAX_ALIAS_LEN);
+        let too_long = "a".repeat(MAX_ALIAS_LEN + 1);
+        let cases = [
+            ("work", true),
+            ("my-project_2.x", true),
+            ("v-next", true),
+            (longest.as_str(), true),
+            (too_long.as_str(), false),
+            ("", false),
+            ("a/b", false),
+            ("a b", false),
+            ("中文", false),
+            ("..", false),
+            (".hidden", false),
+            ("-rf", false),
+            ("system", false),
+            ("20", false),
+            ("v20", false),
+            ("20.12.2", false),
+            ("v18.x", false),
+            ("lts/*", false),
+            ("latest", false),
+        ];
+
+        for (name, valid) in cases {
+            let result = validate_alias_name(name);
+            assert_eq!(result.is_ok(), valid, "{name:?}");
+            if let Err(e) = result {
+                assert_eq!(e.code(), "invalidInput", "{name:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn repair_removes_only_dangling_aliases() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = FnmDir::new(temp.path());
+        fs::create_dir_all(dir.installation_dir("v20.12.2")).unwrap();
+        fs::create_dir_all(dir.aliases_dir()).unwrap();
+        link(
+            &dir.installation_dir("v20.12.2"),
+            &dir.aliases_dir().join("default"),
+        );
+        link(
+            &dir.installation_dir("v16.20.2"),
+            &dir.aliases_dir().join("old"),
+        );
+
+        assert_eq!(repair(&dir).unwrap(), ["old"]);
+        assert!(fs::symlink_metadata(dir.aliases_dir().join("old")).is_err());
+        assert!(dir.aliases_dir().join("default").is_dir());
+        assert!(repair(&dir).unwrap().is_empty());
+    }
+}
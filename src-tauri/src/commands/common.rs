@@ -1,3 +1,7 @@
+use super::fnm_dir::resolve_fnm_dir;
+use super::runner::run_blocking;
+use super::settings::apply_fnm_env_overrides;
+use crate::error::FnmGuiError;
 use std::path::PathBuf;
 use std::process::Command;
 use tauri::command;
@@ -10,7 +14,11 @@ fn get_home_dir() -> Option<PathBuf> {
 
 /// 调试命令：获取 fnm 查找信息
 #[command]
-pub fn debug_fnm_lookup() -> Result<String, String> {
+pub async fn debug_fnm_lookup() -> Result<String, FnmGuiError> {
+    run_blocking(|| Ok(collect_debug_info())).await
+}
+
+fn collect_debug_info() -> String {
     let mut info = String::new();
 
     // 显示 HOME 目录
@@ -28,6 +36,7 @@ pub fn debug_fnm_lookup() -> Result<String, String> {
 
     // 尝试获取 fnm 路径
     info.push_str(&format!("\nget_fnm_path result: {:?}\n", get_fnm_path()));
+    info.push_str(&format!("resolve_fnm_dir result: {:?}\n", resolve_fnm_dir()));
 
     // 如果找到了，尝试执行 fnm --version
     if let Ok(fnm_path) = get_fnm_path() {
@@ -91,12 +100,12 @@ pub fn debug_fnm_lookup() -> Result<String, String> {
         }
     }
 
-    Ok(info)
+    info
 }
 
 /// 获取 fnm 可执行文件的完整路径
 /// 在打包后的 Tauri 应用中，需要使用完整路径来调用 fnm
-pub fn get_fnm_path() -> Result<PathBuf, String> {
+pub fn get_fnm_path() -> Result<PathBuf, FnmGuiError> {
     // 常见的 fnm 安装路径
     let possible_paths = get_possible_fnm_paths();
 
@@ -136,7 +145,7 @@ pub fn get_fnm_path() -> Result<PathBuf, String> {
         }
     }
 
-    Err("未找到 fnm，请确保已正确安装 fnm。\n\n安装方式：\nmacOS: brew install fnm\nWindows: winget install Schniz.fnm\nLinux: curl -fsSL https://fnm.vercel.app/install | bash".to_string())
+    Err(FnmGuiError::FnmNotFound)
 }
 
 /// 获取可能的 fnm 安装路径列表
@@ -197,7 +206,23 @@ fn get_possible_fnm_paths() -> Vec<PathBuf> {
 }
 
 /// 创建一个配置好 PATH 的 fnm Command
-pub fn create_fnm_command() -> Result<Command, String> {
+pub fn create_fnm_command() -> Result<Command, FnmGuiError> {
+    let mut cmd = create_bare_fnm_command()?;
+
+    // 设置 FNM_DIR 环境变量，确保与 GUI 读取的目录一致
+    if let Ok(info) = resolve_fnm_dir() {
+        cmd.env("FNM_DIR", &info.path);
+    }
+
+    // 注入应用内设置的 fnm 配置（镜像、版本文件策略等）
+    apply_fnm_env_overrides(&mut cmd);
+
+    Ok(cmd)
+}
+
+/// 创建只配置了 PATH 的 fnm Command，不设置 FNM_DIR
+/// 用于解析 fnm 目录本身，让 fnm 按自己的默认规则决定目录
+pub fn create_bare_fnm_command() -> Result<Command, FnmGuiError> {
     let fnm_path = get_fnm_path()?;
     let mut cmd = Command::new(&fnm_path);
 
@@ -206,49 +231,9 @@ pub fn create_fnm_command() -> Result<Command, String> {
     let enhanced_path = get_enhanced_path(&current_path);
     cmd.env("PATH", enhanced_path);
 
-    // 设置 FNM_DIR 环境变量，这是 fnm 正常工作所必需的
-    let fnm_dir = get_fnm_dir_path();
-    if !fnm_dir.is_empty() {
-        cmd.env("FNM_DIR", &fnm_dir);
-    }
-
     Ok(cmd)
 }
 
-/// 获取 fnm 数据目录路径
-fn get_fnm_dir_path() -> String {
-    let home = get_home_dir();
-
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join("Library/Application Support/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(local_appdata) = std::env::var("LOCALAPPDATA") {
-            return format!("{}\\fnm", local_appdata);
-        }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join(".local/share/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
-    }
-
-    String::new()
-}
-
 /// 扩展 PATH 环境变量，添加常用的可执行文件路径
 fn get_enhanced_path(current_path: &str) -> String {
     let home = get_home_dir();
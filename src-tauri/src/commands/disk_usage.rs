@@ -0,0 +1,198 @@
+use super::fnm_layout::FnmDir;
+use super::global_packages::{global_modules_dir, BUNDLED_PACKAGES};
+use super::runner::run_blocking;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// 单个已安装版本的占用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDiskUsage {
+    pub version: String,
+    pub total_bytes: u64,
+    /// Node 本身（含自带的 npm、corepack）
+    pub base_bytes: u64,
+    /// 通过 npm install -g 安装的全局包
+    pub global_packages_bytes: u64,
+}
+
+/// fnm 目录之外的缓存目录的占用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheDiskUsage {
+    pub path: String,
+    pub exists: bool,
+    pub bytes: u64,
+}
+
+/// fnm 目录及相关缓存的磁盘占用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsageReport {
+    pub fnm_dir: String,
+    pub versions: Vec<VersionDiskUsage>,
+    /// 全部版本的总占用
+    pub versions_bytes: u64,
+    pub multishells: Option<CacheDiskUsage>,
+    pub corepack_cache: Option<CacheDiskUsage>,
+    pub npm_cache: Option<CacheDiskUsage>,
+    pub total_bytes: u64,
+}
+
+/// 统计 fnm 目录、各版本以及 corepack 和 npm 缓存的磁盘占用
+#[command]
+pub async fn get_disk_usage() -> Result<DiskUsageReport, FnmGuiError> {
+    run_blocking(|| {
+        Ok(disk_usage(
+            &FnmDir::current()?,
+            corepack_cache_dir().as_deref(),
+            npm_cache_dir().as_deref(),
+        ))
+    })
+    .await
+}
+
+pub fn disk_usage(
+    dir: &FnmDir,
+    corepack_cache: Option<&Path>,
+    npm_cache: Option<&Path>,
+) -> DiskUsageReport {
+    let versions: Vec<VersionDiskUsage> = dir
+        .installed_versions()
+        .into_iter()
+        .map(|version| version_usage(dir, version))
+        .collect();
+    let versions_bytes = versions.iter().map(|v| v.total_bytes).sum();
+
+    let multishells = dir.multishell_root().map(cache_usage);
+    let corepack_cache = corepack_cache.map(cache_usage);
+    let npm_cache = npm_cache.map(cache_usage);
+
+    let total_bytes = versions_bytes
+        + [&multishells, &corepack_cache, &npm_cache]
+            .iter()
+            .filter_map(|cache| cache.as_ref().map(|c| c.bytes))
+            .sum::<u64>();
+
+    DiskUsageReport {
+        fnm_dir: dir.root().to_string_lossy().to_string(),
+        versions,
+        versions_bytes,
+        multishells,
+        corepack_cache,
+        npm_cache,
+        total_bytes,
+    }
+}
+
+fn version_usage(dir: &FnmDir, version: String) -> VersionDiskUsage {
+    let total_bytes = dir_size(&dir.node_versions_dir().join(&version));
+
+    let global_packages_bytes = fs::read_dir(global_modules_dir(&dir.installation_dir(&version)))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    !BUNDLED_PACKAGES.contains(&entry.file_name().to_string_lossy().as_ref())
+                })
+                .map(|entry| dir_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0);
+
+    VersionDiskUsage {
+        version,
+        total_bytes,
+        base_bytes: total_bytes.saturating_sub(global_packages_bytes),
+        global_packages_bytes,
+    }
+}
+
+fn cache_usage(path: &Path) -> CacheDiskUsage {
+    CacheDiskUsage {
+        path: path.to_string_lossy().to_string(),
+        exists: path.exists(),
+        bytes: dir_size(path),
+    }
+}
+
+/// corepack 缓存目录：COREPACK_HOME，或默认的 <cache>/node/corepack
+pub fn corepack_cache_dir() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("COREPACK_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(home));
+    }
+
+    let cache = if cfg!(target_os = "windows") {
+        dirs::data_local_dir()
+    } else {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+    };
+    cache.map(|dir| dir.join("node").join("corepack"))
+}
+
+/// npm 缓存目录：npm_config_cache，或默认的 ~/.npm（Windows 上为 %LocalAppData%\npm-cache）
+pub fn npm_cache_dir() -> Option<PathBuf> {
+    if let Some(cache) = std::env::var_os("npm_config_cache").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(cache));
+    }
+
+    if cfg!(target_os = "windows") {
+        dirs::data_local_dir().map(|dir| dir.join("npm-cache"))
+    } else {
+        dirs::home_dir().map(|home| home.join(".npm"))
+    }
+}
+
+/// 目录实际占用的磁盘空间，不跟随符号链接，硬链接只计算一次，无法读取的条目按 0 计算
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut seen = HashSet::new();
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let Ok(metadata) = fs::symlink_metadata(&current) else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&current) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        } else if first_link(&metadata, &mut seen) {
+            total += allocated_size(&metadata);
+        }
+    }
+
+    total
+}
+
+/// 文件的多个硬链接中是否第一次遇到
+#[cfg(unix)]
+fn first_link(metadata: &fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() <= 1 || seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn first_link(_metadata: &fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+/// 文件占用的磁盘块大小，稀疏文件和小文件与文件长度不同
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
@@ -1,5 +1,13 @@
-use super::common::create_fnm_command;
+use super::fnm_dir::{clear_resolved_fnm_dir, resolve_fnm_dir, FnmDirSource};
+use super::fnm_layout::FnmDir;
+use super::operation::{lock_current_fnm_dir, Operation, OperationKind};
+use super::runner::{run_blocking, CommandOutput, FnmRunner, ProcessRunner};
+use super::settings::{fnm_env_overrides, save_fnm_env_overrides, FnmEnvOverrides};
+use super::shell_env::parse_shell_env;
+use crate::error::FnmGuiError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use tauri::command;
 
 /// fnm 环境配置信息
@@ -9,10 +17,15 @@ pub struct FnmEnv {
     pub fnm_dir: String,
     pub node_dist_mirror: String,
     pub version_file_strategy: String,
+    /// FNM_COREPACK_ENABLED：fnm 安装新版本后是否自动执行 corepack enable
     pub corepack_enabled: bool,
+    /// default 版本中是否已由 corepack enable 创建 yarn、pnpm 命令，与 fnm 配置无关
+    pub corepack_shims: bool,
     pub resolve_engines: bool,
     pub arch: String,
     pub loglevel: String,
+    /// 各项配置的来源，不含 corepack_shims
+    pub sources: FnmEnvSources,
 }
 
 impl Default for FnmEnv {
@@ -22,169 +35,309 @@ impl Default for FnmEnv {
             node_dist_mirror: "https://nodejs.org/dist".to_string(),
             version_file_strategy: "local".to_string(),
             corepack_enabled: false,
+            corepack_shims: false,
             resolve_engines: true,
             arch: String::new(),
             loglevel: "info".to_string(),
+            sources: FnmEnvSources::default(),
         }
     }
 }
 
+/// 配置值的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvValueSource {
+    /// fnm 的默认值
+    #[default]
+    FnmDefault,
+    /// 用户 shell 配置文件中的 FNM_* 变量
+    ShellProfile,
+    /// 应用进程的环境变量
+    EnvVar,
+    /// 应用内设置的覆盖值
+    AppOverride,
+}
+
+/// FnmEnv 中每一项配置的来源
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FnmEnvSources {
+    pub fnm_dir: EnvValueSource,
+    pub node_dist_mirror: EnvValueSource,
+    pub version_file_strategy: EnvValueSource,
+    pub corepack_enabled: EnvValueSource,
+    pub resolve_engines: EnvValueSource,
+    pub arch: EnvValueSource,
+    pub loglevel: EnvValueSource,
+}
+
+/// fnm env 输出中与 FnmEnv 对应的变量
+const FNM_ENV_KEYS: [&str; 7] = [
+    "FNM_DIR",
+    "FNM_NODE_DIST_MIRROR",
+    "FNM_VERSION_FILE_STRATEGY",
+    "FNM_COREPACK_ENABLED",
+    "FNM_RESOLVE_ENGINES",
+    "FNM_ARCH",
+    "FNM_LOGLEVEL",
+];
+
 /// 获取 fnm 环境配置
+/// 通过 fnm 自身获取实际生效的配置：优先使用 fnm env --json，失败时解析 shell 格式输出。
+/// GUI 进程通常没有用户 shell 中设置的 FNM_* 变量，因此会先从 shell 配置文件中读取并传给 fnm
 #[command]
-pub fn get_fnm_env() -> Result<FnmEnv, String> {
-    let mut env = FnmEnv::default();
+pub async fn get_fnm_env() -> Result<FnmEnv, FnmGuiError> {
+    run_blocking(|| read_fnm_env(&ProcessRunner)).await
+}
 
-    // 直接设置 fnm 目录
-    env.fnm_dir = get_default_fnm_dir();
+pub fn read_fnm_env(runner: &dyn FnmRunner) -> Result<FnmEnv, FnmGuiError> {
+    let profile_vars = read_shell_profile_vars();
+
+    // 旧版 fnm 不支持 --json，或输出无法解析时，都改为解析 shell 格式输出
+    let json = run_fnm_env(runner, &profile_vars, &["--json"])
+        .and_then(|output| parse_fnm_env_json(&output));
+    let vars = match json {
+        Ok(vars) => vars,
+        Err(_) => {
+            #[cfg(windows)]
+            let shell_args = ["--shell", "cmd"];
+            #[cfg(not(windows))]
+            let shell_args = ["--shell", "bash"];
+
+            let output = run_fnm_env(runner, &profile_vars, &shell_args)?;
+            parse_fnm_env(&output)
+        }
+    };
+
+    let mut env = build_fnm_env(&vars);
+
+    let override_keys = fnm_env_overrides().keys();
+    let source_of = |key: &str| {
+        value_source(key, &override_keys, &profile_vars, |key| {
+            std::env::var(key).ok()
+        })
+    };
+    env.sources = FnmEnvSources {
+        fnm_dir: fnm_dir_source(),
+        node_dist_mirror: source_of("FNM_NODE_DIST_MIRROR"),
+        version_file_strategy: source_of("FNM_VERSION_FILE_STRATEGY"),
+        corepack_enabled: source_of("FNM_COREPACK_ENABLED"),
+        resolve_engines: source_of("FNM_RESOLVE_ENGINES"),
+        arch: source_of("FNM_ARCH"),
+        loglevel: source_of("FNM_LOGLEVEL"),
+    };
 
-    // 设置系统架构
-    env.arch = get_system_arch();
+    // 检测 corepack 是否实际启用（检查 default 版本的 bin 目录）
+    env.corepack_shims = check_corepack_enabled(&env.fnm_dir);
 
-    // 尝试从环境变量获取其他配置
-    if let Ok(mirror) = std::env::var("FNM_NODE_DIST_MIRROR") {
-        env.node_dist_mirror = mirror;
-    }
+    Ok(env)
+}
 
-    if let Ok(strategy) = std::env::var("FNM_VERSION_FILE_STRATEGY") {
-        env.version_file_strategy = strategy;
-    }
+/// 修改 fnm 配置，保存到应用设置中并应用到之后执行的所有 fnm 命令
+/// 传入 None 或空字符串的项会恢复为 fnm 自身的配置
+#[command]
+pub async fn set_fnm_env(overrides: FnmEnvOverrides) -> Result<FnmEnv, FnmGuiError> {
+    run_blocking(move || {
+        let previous = fnm_env_overrides().fnm_dir;
+        if save_fnm_env_overrides(overrides)?.fnm_dir != previous {
+            clear_resolved_fnm_dir();
+        }
+        read_fnm_env(&ProcessRunner)
+    })
+    .await
+}
 
-    if let Ok(corepack) = std::env::var("FNM_COREPACK_ENABLED") {
-        env.corepack_enabled = corepack.to_lowercase() == "true" || corepack == "1";
+/// 执行 fnm env，成功时返回标准输出
+fn run_fnm_env(
+    runner: &dyn FnmRunner,
+    profile_vars: &HashMap<String, String>,
+    args: &[&str],
+) -> Result<String, FnmGuiError> {
+    let mut fnm_args = vec!["env"];
+    fnm_args.extend_from_slice(args);
+
+    // fnm 目录以 resolve_fnm_dir 为准，不使用配置文件中的 FNM_DIR；
+    // 应用内设置的覆盖值已由 create_fnm_command 注入，应用进程的环境变量由 fnm 直接继承，都优先于配置文件
+    let override_keys = fnm_env_overrides().keys();
+    let envs: Vec<(&str, &str)> = profile_vars
+        .iter()
+        .filter(|(key, _)| {
+            key.as_str() != "FNM_DIR"
+                && !override_keys.contains(&key.as_str())
+                && !is_set(std::env::var(key).ok())
+        })
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+
+    let output = runner.run_with_env(&fnm_args, &envs)?;
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("fnm env", None, &output));
     }
 
-    if let Ok(engines) = std::env::var("FNM_RESOLVE_ENGINES") {
-        env.resolve_engines = engines.to_lowercase() != "false" && engines != "0";
-    }
+    Ok(output.stdout)
+}
 
-    if let Ok(loglevel) = std::env::var("FNM_LOGLEVEL") {
-        env.loglevel = loglevel;
+/// 配置值的来源，优先级与 fnm 实际读取到的值一致：应用内覆盖 > 应用进程的环境变量 > shell 配置文件
+fn value_source(
+    key: &str,
+    override_keys: &[&str],
+    profile_vars: &HashMap<String, String>,
+    process_env: impl Fn(&str) -> Option<String>,
+) -> EnvValueSource {
+    if override_keys.contains(&key) {
+        EnvValueSource::AppOverride
+    } else if is_set(process_env(key)) {
+        EnvValueSource::EnvVar
+    } else if profile_vars.contains_key(key) {
+        EnvValueSource::ShellProfile
+    } else {
+        EnvValueSource::FnmDefault
     }
+}
 
-    // 检测 corepack 是否实际启用（检查 default 版本的 bin 目录）
-    env.corepack_enabled = check_corepack_enabled(&env.fnm_dir);
-
-    Ok(env)
+fn is_set(value: Option<String>) -> bool {
+    value.is_some_and(|v| !v.trim().is_empty())
 }
 
-/// 检测 corepack 是否启用
-fn check_corepack_enabled(fnm_dir: &str) -> bool {
-    if fnm_dir.is_empty() {
-        return false;
-    }
+/// 解析 fnm env --json 输出，如 {"FNM_DIR": "...", "FNM_ARCH": "x64", ...}
+fn parse_fnm_env_json(json: &str) -> Result<HashMap<String, String>, FnmGuiError> {
+    let map: HashMap<String, serde_json::Value> =
+        serde_json::from_str(json).map_err(|e| FnmGuiError::parse("fnm env 输出", e))?;
+
+    Ok(map
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            };
+            (key, value)
+        })
+        .collect())
+}
 
-    let default_alias = std::path::PathBuf::from(fnm_dir).join("aliases").join("default");
-    if !default_alias.exists() {
-        return false;
-    }
+/// 读取用户 shell 配置文件中设置的 FNM_* 变量，后出现的值覆盖先出现的
+pub fn read_shell_profile_vars() -> HashMap<String, String> {
+    let mut vars = HashMap::new();
 
-    if let Ok(version_path) = std::fs::read_link(&default_alias) {
-        // 检查是否有 yarn 或 pnpm 在 bin 目录（corepack enable 会创建这些）
-        let yarn_path = version_path.join("bin").join("yarn");
-        let pnpm_path = version_path.join("bin").join("pnpm");
-        return yarn_path.exists() || pnpm_path.exists();
+    for profile in get_shell_profile_paths() {
+        let Ok(content) = std::fs::read_to_string(&profile) else {
+            continue;
+        };
+        vars.extend(parse_fnm_env(&content));
     }
 
-    false
+    vars
 }
 
-/// 解析 fnm env 输出
-fn parse_fnm_env(env_str: &str) -> Result<FnmEnv, String> {
-    let mut env = FnmEnv::default();
-
-    for line in env_str.lines() {
-        // 处理 export 格式: export FNM_DIR="/path/to/fnm"
-        // 或者简单格式: FNM_DIR="/path/to/fnm"
-        let line = line.trim();
-
-        if let Some(value) = extract_env_value(line, "FNM_DIR") {
-            env.fnm_dir = value;
-        } else if let Some(value) = extract_env_value(line, "FNM_NODE_DIST_MIRROR") {
-            env.node_dist_mirror = value;
-        } else if let Some(value) = extract_env_value(line, "FNM_VERSION_FILE_STRATEGY") {
-            env.version_file_strategy = value;
-        } else if let Some(value) = extract_env_value(line, "FNM_COREPACK_ENABLED") {
-            env.corepack_enabled = value.to_lowercase() == "true" || value == "1";
-        } else if let Some(value) = extract_env_value(line, "FNM_RESOLVE_ENGINES") {
-            env.resolve_engines = value.to_lowercase() == "true" || value == "1";
-        } else if let Some(value) = extract_env_value(line, "FNM_ARCH") {
-            env.arch = value;
-        } else if let Some(value) = extract_env_value(line, "FNM_LOGLEVEL") {
-            env.loglevel = value;
-        }
-    }
+/// 可能设置 FNM_* 变量的 shell 配置文件，按 shell 加载顺序排列
+fn get_shell_profile_paths() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
 
-    // 如果未从 fnm env 获取到目录，尝试获取默认路径
-    if env.fnm_dir.is_empty() {
-        env.fnm_dir = get_default_fnm_dir();
+    #[cfg(windows)]
+    {
+        [
+            "Documents\\WindowsPowerShell\\Microsoft.PowerShell_profile.ps1",
+            "Documents\\PowerShell\\Microsoft.PowerShell_profile.ps1",
+        ]
+        .iter()
+        .map(|name| home.join(name))
+        .collect()
     }
 
-    // 获取系统架构
-    if env.arch.is_empty() {
-        env.arch = get_system_arch();
+    #[cfg(not(windows))]
+    {
+        [
+            ".profile",
+            ".bash_profile",
+            ".bashrc",
+            ".zshenv",
+            ".zprofile",
+            ".zshrc",
+            ".config/fish/config.fish",
+        ]
+        .iter()
+        .map(|name| home.join(name))
+        .collect()
     }
-
-    Ok(env)
 }
 
-/// 从环境变量行中提取值
-fn extract_env_value(line: &str, key: &str) -> Option<String> {
-    // 匹配格式: export KEY="value" 或 KEY="value" 或 set KEY="value"
-    let patterns = [
-        format!("export {}=", key),
-        format!("set {}=", key),
-        format!("{}=", key),
-    ];
-
-    for pattern in patterns {
-        if line.contains(&pattern) {
-            let value = line
-                .split(&pattern)
-                .nth(1)?
-                .trim()
-                .trim_matches('"')
-                .trim_matches('\'')
-                .trim_end_matches(';')
-                .to_string();
-            return Some(value);
-        }
+/// 根据 fnm env 的变量构建 FnmEnv，缺失的项使用默认值
+fn build_fnm_env(vars: &HashMap<String, String>) -> FnmEnv {
+    let mut env = FnmEnv {
+        arch: get_system_arch(),
+        ..FnmEnv::default()
+    };
+
+    let is_true = |value: &str| value.eq_ignore_ascii_case("true") || value == "1";
+
+    env.fnm_dir = match vars.get("FNM_DIR").filter(|v| !v.is_empty()) {
+        Some(value) => value.clone(),
+        None => get_default_fnm_dir(),
+    };
+    if let Some(value) = vars.get("FNM_NODE_DIST_MIRROR") {
+        env.node_dist_mirror = value.clone();
+    }
+    if let Some(value) = vars.get("FNM_VERSION_FILE_STRATEGY") {
+        env.version_file_strategy = value.clone();
+    }
+    if let Some(value) = vars.get("FNM_COREPACK_ENABLED") {
+        env.corepack_enabled = is_true(value);
+    }
+    if let Some(value) = vars.get("FNM_RESOLVE_ENGINES") {
+        env.resolve_engines = is_true(value);
+    }
+    if let Some(value) = vars.get("FNM_ARCH").filter(|v| !v.is_empty()) {
+        env.arch = value.clone();
+    }
+    if let Some(value) = vars.get("FNM_LOGLEVEL") {
+        env.loglevel = value.clone();
     }
 
-    None
+    env
 }
 
-/// 获取默认的 fnm 目录
-fn get_default_fnm_dir() -> String {
-    let home = dirs::home_dir();
-
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join("Library/Application Support/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
+/// 检测 corepack 是否启用
+fn check_corepack_enabled(fnm_dir: &str) -> bool {
+    if fnm_dir.is_empty() {
+        return false;
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(local_appdata) = std::env::var("LOCALAPPDATA") {
-            return format!("{}\\fnm", local_appdata);
-        }
+    let dir = FnmDir::new(fnm_dir);
+    if let Some(version) = dir.default_version() {
+        // 检查是否有 yarn 或 pnpm 在 bin 目录（corepack enable 会创建这些）
+        let bin = dir.installation_dir(&version).join("bin");
+        return bin.join("yarn").exists() || bin.join("pnpm").exists();
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join(".local/share/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
+    false
+}
+
+/// 解析 fnm env 的 shell 格式输出（bash/zsh/fish/PowerShell/cmd），返回其中的 FNM_* 变量
+fn parse_fnm_env(env_str: &str) -> HashMap<String, String> {
+    parse_shell_env(env_str)
+        .vars
+        .into_iter()
+        .filter(|(key, _)| FNM_ENV_KEYS.contains(&key.as_str()))
+        .collect()
+}
+
+/// fnm 执行时使用的 FNM_DIR 由 resolve_fnm_dir 决定，按其来源归类
+fn fnm_dir_source() -> EnvValueSource {
+    match resolve_fnm_dir().map(|info| info.source) {
+        Ok(FnmDirSource::AppOverride) => EnvValueSource::AppOverride,
+        Ok(FnmDirSource::EnvVar) => EnvValueSource::EnvVar,
+        Ok(FnmDirSource::ShellProfile) => EnvValueSource::ShellProfile,
+        _ => EnvValueSource::FnmDefault,
     }
+}
 
-    String::new()
+/// 获取解析出的 fnm 目录，无法确定时返回空字符串
+fn get_default_fnm_dir() -> String {
+    resolve_fnm_dir().map(|info| info.path).unwrap_or_default()
 }
 
 /// 获取系统架构
@@ -207,37 +360,37 @@ fn get_system_arch() -> String {
 
 /// 切换 Corepack 状态
 #[command]
-pub fn toggle_corepack(enable: bool) -> Result<String, String> {
+pub async fn toggle_corepack(enable: bool) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::global(OperationKind::Corepack)).await?;
+    run_blocking(move || run_corepack(enable)).await
+}
+
+/// 对默认版本执行 corepack enable/disable
+fn run_corepack(enable: bool) -> Result<String, FnmGuiError> {
     // 获取 fnm 目录
-    let fnm_dir = get_default_fnm_dir();
-    if fnm_dir.is_empty() {
-        return Err("无法确定 fnm 目录".to_string());
-    }
+    let fnm_dir = resolve_fnm_dir()?.path;
 
     // 读取 default 别名指向的版本
-    let default_alias = std::path::PathBuf::from(&fnm_dir).join("aliases").join("default");
-    if !default_alias.exists() {
-        return Err("请先设置一个默认 Node.js 版本".to_string());
-    }
-
-    let version_path = std::fs::read_link(&default_alias)
-        .map_err(|_| "无法读取默认版本".to_string())?;
+    let dir = FnmDir::new(&fnm_dir);
+    let version = dir.default_version().ok_or(FnmGuiError::NoDefaultVersion)?;
+    let version_path = dir.installation_dir(&version);
 
     // 构建 corepack 路径
     let corepack_path = version_path.join("bin").join("corepack");
     if !corepack_path.exists() {
-        return Err("找不到 corepack，请确保 Node.js 版本 >= 16.9.0".to_string());
+        return Err(FnmGuiError::CorepackMissing);
     }
 
     // 执行 corepack enable/disable
+    let action = if enable { "enable" } else { "disable" };
     let output = std::process::Command::new(&corepack_path)
-        .arg(if enable { "enable" } else { "disable" })
+        .arg(action)
         .output()
-        .map_err(|e| format!("执行 corepack 命令失败: {}", e))?;
+        .map(CommandOutput::from)
+        .map_err(|e| FnmGuiError::spawn("corepack", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(stderr.to_string());
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output(format!("corepack {}", action), None, &output));
     }
 
     Ok(if enable {
@@ -246,3 +399,79 @@ pub fn toggle_corepack(enable: bool) -> Result<String, String> {
         "Corepack 已禁用".to_string()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::runner::ScriptedRunner;
+
+    #[test]
+    fn build_fnm_env_uses_reported_values() {
+        let vars: HashMap<String, String> = [
+            ("FNM_DIR", "/data/fnm"),
+            ("FNM_COREPACK_ENABLED", "true"),
+            ("FNM_VERSION_FILE_STRATEGY", "recursive"),
+            ("FNM_RESOLVE_ENGINES", "false"),
+            ("FNM_ARCH", "arm64"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+        let env = build_fnm_env(&vars);
+        assert_eq!(env.fnm_dir, "/data/fnm");
+        assert!(env.corepack_enabled);
+        assert!(!env.corepack_shims);
+        assert_eq!(env.version_file_strategy, "recursive");
+        assert!(!env.resolve_engines);
+        assert_eq!(env.arch, "arm64");
+        assert_eq!(env.node_dist_mirror, "https://nodejs.org/dist");
+        assert_eq!(env.loglevel, "info");
+    }
+
+    #[test]
+    fn value_source_follows_override_env_and_profile_order() {
+        let profile_vars: HashMap<String, String> = [
+            ("FNM_ARCH", "arm64"),
+            ("FNM_LOGLEVEL", "quiet"),
+            ("FNM_NODE_DIST_MIRROR", "https://npmmirror.com/mirrors/node"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let process_env = |key: &str| match key {
+            "FNM_ARCH" | "FNM_COREPACK_ENABLED" => Some("x64".to_string()),
+            "FNM_LOGLEVEL" => Some(" ".to_string()),
+            _ => None,
+        };
+        let source_of =
+            |key: &str| value_source(key, &["FNM_NODE_DIST_MIRROR"], &profile_vars, process_env);
+
+        assert_eq!(
+            source_of("FNM_NODE_DIST_MIRROR"),
+            EnvValueSource::AppOverride
+        );
+        assert_eq!(source_of("FNM_ARCH"), EnvValueSource::EnvVar);
+        assert_eq!(source_of("FNM_COREPACK_ENABLED"), EnvValueSource::EnvVar);
+        assert_eq!(source_of("FNM_LOGLEVEL"), EnvValueSource::ShellProfile);
+        assert_eq!(source_of("FNM_RESOLVE_ENGINES"), EnvValueSource::FnmDefault);
+    }
+
+    #[test]
+    fn falls_back_to_shell_output_when_json_is_unparsable() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = ScriptedRunner::new(dir.path())
+            .on(
+                &["env", "--json"],
+                CommandOutput::success("FNM_ARCH=arm64\n"),
+            )
+            .on(
+                &["env"],
+                CommandOutput::success("export FNM_VERSION_FILE_STRATEGY=\"recursive\"\n"),
+            );
+
+        let env = read_fnm_env(&runner).unwrap();
+        assert_eq!(env.version_file_strategy, "recursive");
+        assert_eq!(runner.invocations().len(), 2);
+    }
+}
@@ -0,0 +1,245 @@
+use super::common::create_bare_fnm_command;
+use super::env::read_shell_profile_vars;
+use super::settings::fnm_env_overrides;
+use super::shell_env::parse_shell_env;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// fnm 目录的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FnmDirSource {
+    /// 应用内设置的 FNM_DIR
+    AppOverride,
+    /// 应用进程的 FNM_DIR 环境变量
+    EnvVar,
+    /// 用户 shell 配置文件中设置的 FNM_DIR
+    ShellProfile,
+    /// fnm env 输出的 FNM_DIR
+    FnmEnv,
+    /// 旧版 fnm 使用的 ~/.fnm
+    Legacy,
+    /// $XDG_DATA_HOME/fnm
+    XdgDataHome,
+    /// 平台默认数据目录
+    PlatformDefault,
+}
+
+/// 解析出的 fnm 目录及其来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FnmDirInfo {
+    pub path: String,
+    pub source: FnmDirSource,
+}
+
+/// 缓存解析结果，避免每次调用都执行 fnm env；应用内的 FNM_DIR 设置变化时由 clear_resolved_fnm_dir 清除
+static RESOLVED_FNM_DIR: Mutex<Option<FnmDirInfo>> = Mutex::new(None);
+
+/// 解析 fnm 目录，优先级依次为：
+/// 1. 应用内设置的 FNM_DIR（set_fnm_env）
+/// 2. 应用进程的 FNM_DIR 环境变量
+/// 3. 用户 shell 配置文件中设置的 FNM_DIR（从 Dock、开始菜单启动的 GUI 进程不会加载这些文件）
+/// 4. fnm env 输出的 FNM_DIR（即 fnm 自身实际使用的目录）
+/// 5. 已存在的旧版目录 ~/.fnm
+/// 6. $XDG_DATA_HOME/fnm
+/// 7. 平台默认目录（macOS: ~/Library/Application Support/fnm，
+///    Windows: %LOCALAPPDATA%\fnm，Linux: ~/.local/share/fnm）
+///
+/// 第 5-7 步与 fnm 自身的默认规则一致，用于 fnm 不可用时的回退
+pub fn resolve_fnm_dir() -> Result<FnmDirInfo, FnmGuiError> {
+    if let Some(info) = RESOLVED_FNM_DIR.lock().unwrap().clone() {
+        return Ok(info);
+    }
+
+    let info = resolve_uncached()?;
+    *RESOLVED_FNM_DIR.lock().unwrap() = Some(info.clone());
+    Ok(info)
+}
+
+/// 清除缓存的解析结果，下次调用 resolve_fnm_dir 时重新解析
+pub fn clear_resolved_fnm_dir() {
+    *RESOLVED_FNM_DIR.lock().unwrap() = None;
+}
+
+/// 获取 fnm 目录路径
+pub fn fnm_dir_path() -> Result<PathBuf, FnmGuiError> {
+    resolve_fnm_dir().map(|info| PathBuf::from(info.path))
+}
+
+fn resolve_uncached() -> Result<FnmDirInfo, FnmGuiError> {
+    let found = |path: PathBuf, source: FnmDirSource| FnmDirInfo {
+        path: path.to_string_lossy().to_string(),
+        source,
+    };
+
+    let home = dirs::home_dir();
+
+    let explicit = explicit_fnm_dir([
+        (fnm_env_overrides().fnm_dir, FnmDirSource::AppOverride),
+        (non_empty_env("FNM_DIR"), FnmDirSource::EnvVar),
+        (
+            fnm_dir_from_shell_profile(home.as_deref()),
+            FnmDirSource::ShellProfile,
+        ),
+    ]);
+    if let Some((dir, source)) = explicit {
+        return Ok(found(PathBuf::from(dir), source));
+    }
+
+    if let Some(dir) = fnm_dir_from_fnm_env() {
+        return Ok(found(PathBuf::from(dir), FnmDirSource::FnmEnv));
+    }
+
+    if let Some(legacy) = home.as_ref().map(|h| h.join(".fnm")) {
+        // ~/.fnm 也可能只存放 fnm 可执行文件（官方安装脚本），需要包含 node-versions 才算旧版布局
+        if legacy.join("node-versions").is_dir() {
+            return Ok(found(legacy, FnmDirSource::Legacy));
+        }
+    }
+
+    if let Some(xdg) = non_empty_env("XDG_DATA_HOME") {
+        return Ok(found(PathBuf::from(xdg).join("fnm"), FnmDirSource::XdgDataHome));
+    }
+
+    platform_default_dir(home)
+        .map(|dir| found(dir, FnmDirSource::PlatformDefault))
+        .ok_or(FnmGuiError::FnmDirUnresolvable)
+}
+
+/// 按顺序取第一个非空的显式 FNM_DIR 设置
+fn explicit_fnm_dir(
+    candidates: [(Option<String>, FnmDirSource); 3],
+) -> Option<(String, FnmDirSource)> {
+    candidates.into_iter().find_map(|(dir, source)| {
+        dir.filter(|dir| !dir.trim().is_empty())
+            .map(|dir| (dir, source))
+    })
+}
+
+/// 读取 shell 配置文件中的 FNM_DIR，展开开头的 ~ 和 $HOME；
+/// 引用了其他变量时无法确定实际路径，忽略
+fn fnm_dir_from_shell_profile(home: Option<&Path>) -> Option<String> {
+    let dir = read_shell_profile_vars().remove("FNM_DIR")?;
+    expand_home(&dir, home?)
+}
+
+fn expand_home(value: &str, home: &Path) -> Option<String> {
+    let rest = ["~", "$HOME", "${HOME}"]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .filter(|rest| rest.is_empty() || rest.starts_with(['/', '\\']));
+
+    let expanded = match rest {
+        Some(rest) => format!("{}{}", home.to_string_lossy(), rest),
+        None => value.to_string(),
+    };
+    (!expanded.contains('$')).then_some(expanded)
+}
+
+fn non_empty_env(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// 执行 fnm env 并读取其中的 FNM_DIR
+fn fnm_dir_from_fnm_env() -> Option<String> {
+    let mut cmd = create_bare_fnm_command().ok()?;
+    cmd.arg("env");
+
+    // GUI 进程没有可供推断的 shell，需要显式指定
+    #[cfg(windows)]
+    cmd.arg("--shell").arg("cmd");
+    #[cfg(not(windows))]
+    cmd.arg("--shell").arg("bash");
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let env_str = String::from_utf8_lossy(&output.stdout);
+    parse_shell_env(&env_str)
+        .vars
+        .remove("FNM_DIR")
+        .filter(|dir| !dir.is_empty())
+}
+
+fn platform_default_dir(home: Option<PathBuf>) -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        home.map(|h| h.join("Library/Application Support/fnm"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = home;
+        std::env::var("LOCALAPPDATA")
+            .ok()
+            .map(|appdata| PathBuf::from(appdata).join("fnm"))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        home.map(|h| h.join(".local/share/fnm"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(path: &str) -> Option<String> {
+        Some(path.to_string())
+    }
+
+    #[test]
+    fn explicit_settings_take_precedence_in_order() {
+        use FnmDirSource::*;
+
+        let cases = [
+            (
+                [dir("/app"), dir("/env"), dir("/profile")],
+                Some(("/app", AppOverride)),
+            ),
+            ([None, dir("/env"), dir("/profile")], Some(("/env", EnvVar))),
+            (
+                [dir(""), dir(" "), dir("/profile")],
+                Some(("/profile", ShellProfile)),
+            ),
+            ([None, None, None], None),
+        ];
+
+        for ([app, env, profile], expected) in cases {
+            let resolved =
+                explicit_fnm_dir([(app, AppOverride), (env, EnvVar), (profile, ShellProfile)]);
+            let expected = expected.map(|(path, source)| (path.to_string(), source));
+            assert_eq!(resolved, expected);
+        }
+    }
+
+    #[test]
+    fn expands_home_in_profile_values() {
+        let home = Path::new("/home/me");
+
+        assert_eq!(
+            expand_home("~/.fnm", home).as_deref(),
+            Some("/home/me/.fnm")
+        );
+        assert_eq!(
+            expand_home("$HOME/.fnm", home).as_deref(),
+            Some("/home/me/.fnm")
+        );
+        assert_eq!(
+            expand_home("${HOME}/fnm", home).as_deref(),
+            Some("/home/me/fnm")
+        );
+        assert_eq!(expand_home("/opt/fnm", home).as_deref(), Some("/opt/fnm"));
+        assert_eq!(
+            expand_home("~other/fnm", home).as_deref(),
+            Some("~other/fnm")
+        );
+        assert_eq!(expand_home("$XDG_DATA_HOME/fnm", home), None);
+    }
+}
@@ -0,0 +1,446 @@
+use super::fnm_dir::fnm_dir_path;
+use super::semver::NodeVersion;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// 别名的存储形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AliasKind {
+    /// 指向版本安装目录的符号链接（Windows 上为 junction）
+    Symlink,
+    /// 内容为目标路径或版本号的普通文件
+    File,
+}
+
+/// aliases 目录下的一个别名
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasEntry {
+    pub name: String,
+    pub kind: AliasKind,
+    /// 链接或文件中记录的原始目标
+    pub target: String,
+    /// 从目标中解析出的版本号，如 v20.12.2
+    pub version: Option<String>,
+    /// 目标版本目录已不存在
+    pub dangling: bool,
+}
+
+/// fnm_multishells 下某个 shell 会话的版本链接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultishellEntry {
+    pub path: String,
+    pub version: Option<String>,
+    pub dangling: bool,
+}
+
+/// 直接读取磁盘上的 fnm 目录，不调用 fnm：
+/// <fnm_dir>/node-versions/<version>/installation 为各版本的安装目录，
+/// <fnm_dir>/aliases/<name> 为指向安装目录的符号链接
+#[derive(Debug, Clone)]
+pub struct FnmDir {
+    root: PathBuf,
+    multishell_root: Option<PathBuf>,
+}
+
+impl FnmDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FnmDir {
+            root: root.into(),
+            multishell_root: default_multishell_root(),
+        }
+    }
+
+    /// 使用 resolve_fnm_dir 解析出的目录
+    pub fn current() -> Result<Self, FnmGuiError> {
+        fnm_dir_path().map(FnmDir::new)
+    }
+
+    /// 指定 fnm_multishells 目录
+    pub fn with_multishell_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.multishell_root = Some(root.into());
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// fnm_multishells 目录，无法确定时为 None
+    pub fn multishell_root(&self) -> Option<&Path> {
+        self.multishell_root.as_deref()
+    }
+
+    pub fn node_versions_dir(&self) -> PathBuf {
+        self.root.join("node-versions")
+    }
+
+    pub fn aliases_dir(&self) -> PathBuf {
+        self.root.join("aliases")
+    }
+
+    pub fn installation_dir(&self, version: &str) -> PathBuf {
+        self.node_versions_dir().join(version).join("installation")
+    }
+
+    pub fn is_installed(&self, version: &str) -> bool {
+        self.installation_dir(version).is_dir()
+    }
+
+    /// 已安装的版本，按版本号从低到高排列（与 fnm list 一致）
+    pub fn installed_versions(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.node_versions_dir()) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().join("installation").is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+            .filter(|name| name.starts_with('v'))
+            .collect();
+
+        versions.sort_by(|a, b| compare_versions(a, b));
+        versions
+    }
+
+    /// 全部别名，按名称排列
+    pub fn aliases(&self) -> Vec<AliasEntry> {
+        let Ok(entries) = fs::read_dir(self.aliases_dir()) else {
+            return Vec::new();
+        };
+
+        let mut aliases: Vec<AliasEntry> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_str()?.to_string();
+                self.read_alias(&name)
+            })
+            .collect();
+
+        aliases.sort_by(|a, b| a.name.cmp(&b.name));
+        aliases
+    }
+
+    /// 读取单个别名，不存在时返回 None
+    pub fn alias(&self, name: &str) -> Option<AliasEntry> {
+        self.read_alias(name)
+    }
+
+    /// default 别名指向的版本
+    pub fn default_version(&self) -> Option<String> {
+        self.alias("default")
+            .filter(|alias| !alias.dangling)
+            .and_then(|alias| alias.version)
+    }
+
+    /// 各 shell 会话当前使用的版本
+    pub fn multishells(&self) -> Vec<MultishellEntry> {
+        let Some(ref root) = self.multishell_root else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(root) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let target = fs::read_link(&path).ok()?;
+                Some(MultishellEntry {
+                    path: path.to_string_lossy().to_string(),
+                    version: version_from_target(&target),
+                    dangling: !path.exists(),
+                })
+            })
+            .collect()
+    }
+
+    fn read_alias(&self, name: &str) -> Option<AliasEntry> {
+        let path = self.aliases_dir().join(name);
+        let metadata = fs::symlink_metadata(&path).ok()?;
+
+        let (kind, target) = if metadata.file_type().is_symlink() {
+            (AliasKind::Symlink, fs::read_link(&path).ok()?)
+        } else if metadata.is_file() {
+            let content = fs::read_to_string(&path).ok()?;
+            (AliasKind::File, PathBuf::from(content.trim()))
+        } else {
+            // Windows 上的 junction 表现为目录
+            match fs::read_link(&path) {
+                Ok(target) => (AliasKind::Symlink, target),
+                Err(_) => return None,
+            }
+        };
+
+        let version = version_from_target(&target);
+        let dangling = match kind {
+            AliasKind::Symlink => !path.exists(),
+            AliasKind::File => !version.as_deref().is_some_and(|v| self.is_installed(v)),
+        };
+
+        Some(AliasEntry {
+            name: name.to_string(),
+            kind,
+            target: target.to_string_lossy().to_string(),
+            version,
+            dangling,
+        })
+    }
+}
+
+/// 从链接目标中提取版本号：
+/// .../node-versions/v20.12.2/installation、.../node-versions/v20.12.2 或直接为 v20.12.2
+fn version_from_target(target: &Path) -> Option<String> {
+    let components: Vec<&str> = target
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+
+    let version = match components.iter().position(|c| *c == "node-versions") {
+        Some(index) => components.get(index + 1)?,
+        None => match components.as_slice() {
+            [.., version, "installation"] => version,
+            [.., version] => version,
+            [] => return None,
+        },
+    };
+
+    version.starts_with('v').then(|| version.to_string())
+}
+
+/// 按 semver 规则比较版本号，如 v9.0.0 < v10.0.0、v22.0.0-rc.1 < v22.0.0
+/// 无法解析的版本号按数字逐段比较
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    if let (Some(x), Some(y)) = (NodeVersion::parse(a), NodeVersion::parse(b)) {
+        return x.cmp(&y).then_with(|| a.cmp(b));
+    }
+
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b)).then_with(|| a.cmp(b))
+}
+
+/// 版本所属的发布线：v20.12.2 为 "20"，0.x 时代的 v0.12.18 为 "0.12"
+pub fn release_line(version: &str) -> String {
+    let mut parts = version.trim_start_matches('v').split('.');
+    match (parts.next(), parts.next()) {
+        (Some("0"), Some(minor)) => format!("0.{}", minor),
+        (Some(major), _) => major.to_string(),
+        _ => version.to_string(),
+    }
+}
+
+/// fnm 存放 shell 会话链接的默认目录
+fn default_multishell_root() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        dirs::cache_dir().map(|dir| dir.join("fnm_multishells"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        dirs::data_local_dir().map(|dir| dir.join("fnm_multishells"))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        std::env::var("XDG_RUNTIME_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(dir).join("fnm_multishells"))
+            .filter(|dir| dir.is_dir())
+            .or_else(|| dirs::state_dir().map(|dir| dir.join("fnm_multishells")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 临时 fnm 目录，已安装 installed 中的版本
+    fn fixture(installed: &[&str]) -> (TempDir, FnmDir) {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = FnmDir::new(temp.path().join("fnm"))
+            .with_multishell_root(temp.path().join("fnm_multishells"));
+        for version in installed {
+            fs::create_dir_all(dir.installation_dir(version)).unwrap();
+        }
+        fs::create_dir_all(dir.aliases_dir()).unwrap();
+        fs::create_dir_all(dir.multishell_root().unwrap()).unwrap();
+        (temp, dir)
+    }
+
+    #[cfg(unix)]
+    fn link(target: &Path, link: &Path) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    #[cfg(windows)]
+    fn link(target: &Path, link: &Path) {
+        std::os::windows::fs::symlink_dir(target, link).unwrap();
+    }
+
+    #[test]
+    fn lists_installed_versions_in_semver_order() {
+        let (_temp, dir) = fixture(&["v10.0.0", "v9.11.2", "v22.0.0", "v22.0.0-rc.1"]);
+        // 没有 installation 子目录的版本和非版本目录不算已安装
+        fs::create_dir_all(dir.node_versions_dir().join("v18.0.0")).unwrap();
+        fs::create_dir_all(dir.node_versions_dir().join(".downloads/installation")).unwrap();
+
+        assert_eq!(
+            dir.installed_versions(),
+            ["v9.11.2", "v10.0.0", "v22.0.0-rc.1", "v22.0.0"]
+        );
+        assert!(dir.is_installed("v10.0.0"));
+        assert!(!dir.is_installed("v18.0.0"));
+    }
+
+    #[test]
+    fn reads_symlink_and_file_aliases() {
+        let (_temp, dir) = fixture(&["v20.12.2", "v22.21.1"]);
+        link(&dir.installation_dir("v22.21.1"), &dir.aliases_dir().join("default"));
+        fs::write(
+            dir.aliases_dir().join("lts-iron"),
+            format!("{}\n", dir.installation_dir("v20.12.2").display()),
+        )
+        .unwrap();
+        fs::write(dir.aliases_dir().join("work"), "v20.12.2").unwrap();
+
+        let aliases = dir.aliases();
+        let summary: Vec<(&str, AliasKind, Option<&str>, bool)> = aliases
+            .iter()
+            .map(|a| (a.name.as_str(), a.kind, a.version.as_deref(), a.dangling))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("default", AliasKind::Symlink, Some("v22.21.1"), false),
+                ("lts-iron", AliasKind::File, Some("v20.12.2"), false),
+                ("work", AliasKind::File, Some("v20.12.2"), false),
+            ]
+        );
+        assert_eq!(aliases[0].target, dir.installation_dir("v22.21.1").to_string_lossy());
+        assert_eq!(dir.default_version().as_deref(), Some("v22.21.1"));
+        assert_eq!(dir.alias("work").unwrap().target, "v20.12.2");
+        assert!(dir.alias("missing").is_none());
+    }
+
+    #[test]
+    fn marks_dangling_aliases() {
+        let (_temp, dir) = fixture(&["v20.12.2"]);
+        link(&dir.installation_dir("v16.20.2"), &dir.aliases_dir().join("default"));
+        fs::write(dir.aliases_dir().join("old"), "v14.21.3").unwrap();
+        fs::write(dir.aliases_dir().join("broken"), "not-a-version").unwrap();
+
+        let dangling: Vec<(String, Option<String>, bool)> = dir
+            .aliases()
+            .into_iter()
+            .map(|a| (a.name, a.version, a.dangling))
+            .collect();
+        assert_eq!(
+            dangling,
+            [
+                ("broken".to_string(), None, true),
+                ("default".to_string(), Some("v16.20.2".to_string()), true),
+                ("old".to_string(), Some("v14.21.3".to_string()), true),
+            ]
+        );
+        // 指向已卸载版本的 default 视为未设置
+        assert_eq!(dir.default_version(), None);
+    }
+
+    #[test]
+    fn reads_multishell_links() {
+        let (_temp, dir) = fixture(&["v20.12.2"]);
+        let root = dir.multishell_root().unwrap().to_path_buf();
+        link(&dir.installation_dir("v20.12.2"), &root.join("1234_1700000000000"));
+        link(&dir.installation_dir("v18.0.0"), &root.join("5678_1700000000001"));
+        // 不是链接的条目会被忽略
+        fs::write(root.join("stray"), "").unwrap();
+
+        let mut shells: Vec<(Option<String>, bool)> = dir
+            .multishells()
+            .into_iter()
+            .map(|s| (s.version, s.dangling))
+            .collect();
+        shells.sort();
+        assert_eq!(
+            shells,
+            [
+                (Some("v18.0.0".to_string()), true),
+                (Some("v20.12.2".to_string()), false),
+            ]
+        );
+
+        let missing = FnmDir::new(dir.root()).with_multishell_root(root.join("nope"));
+        assert!(missing.multishells().is_empty());
+    }
+
+    #[test]
+    fn extracts_version_from_alias_targets() {
+        let cases = [
+            ("/home/dev/.fnm/node-versions/v20.12.2/installation", Some("v20.12.2")),
+            ("/home/dev/.fnm/node-versions/v20.12.2", Some("v20.12.2")),
+            ("../node-versions/v18.0.0/installation", Some("v18.0.0")),
+            ("v22.21.1", Some("v22.21.1")),
+            ("/opt/node/v22.21.1/installation", Some("v22.21.1")),
+            ("/usr/local/bin/node", None),
+            ("", None),
+        ];
+        for (target, expected) in cases {
+            assert_eq!(version_from_target(Path::new(target)).as_deref(), expected, "{target}");
+        }
+    }
+
+    #[test]
+    fn compares_versions() {
+        let ordered = [
+            "v0.10.48",
+            "v0.12.18",
+            "v4.9.1",
+            "v9.11.2",
+            "v10.0.0",
+            "v22.0.0-rc.1",
+            "v22.0.0",
+            "v22.21.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert_eq!(compare_versions(pair[0], pair[1]), Ordering::Less, "{pair:?}");
+            assert_eq!(compare_versions(pair[1], pair[0]), Ordering::Greater, "{pair:?}");
+        }
+        assert_eq!(compare_versions("v20.1.0", "v20.1.0"), Ordering::Equal);
+        // 无法解析的版本号按数字逐段比较
+        assert_eq!(compare_versions("v20.1", "v20.1.1"), Ordering::Less);
+        assert_eq!(compare_versions("v9.x", "v10.x"), Ordering::Less);
+    }
+
+    #[test]
+    fn finds_release_line() {
+        let cases = [
+            ("v20.12.2", "20"),
+            ("22.0.0-rc.1", "22"),
+            ("v0.12.18", "0.12"),
+            ("v0.10", "0.10"),
+            ("v4", "4"),
+            ("system", "system"),
+        ];
+        for (version, line) in cases {
+            assert_eq!(release_line(version), line, "{version}");
+        }
+    }
+}
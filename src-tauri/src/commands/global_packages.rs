@@ -0,0 +1,154 @@
+use super::fnm_layout::FnmDir;
+use super::runner::run_blocking;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// Node 自带的全局包，属于基础安装
+pub const BUNDLED_PACKAGES: [&str; 2] = ["npm", "corepack"];
+
+/// 某个版本下的全局 npm 包
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalPackage {
+    /// 包名，带 scope 的包如 @vue/cli
+    pub name: String,
+    pub version: Option<String>,
+    /// 随 Node 一起安装的 npm、corepack
+    pub bundled: bool,
+    /// 通过 npm link 链接到本地目录的包
+    pub linked: bool,
+    /// package.json 中 bin 声明的命令
+    pub bins: Vec<String>,
+}
+
+/// 某个已安装版本的全部全局包
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionGlobalPackages {
+    pub version: String,
+    pub packages: Vec<GlobalPackage>,
+}
+
+/// 列出全局 npm 包，version 为已安装的版本号（可省略 v 前缀），为空时列出所有已安装版本
+/// 直接读取 node_modules 下的 package.json，不启动 npm
+#[command]
+pub async fn list_global_packages(
+    version: Option<String>,
+) -> Result<Vec<VersionGlobalPackages>, FnmGuiError> {
+    run_blocking(move || inventory(&FnmDir::current()?, version.as_deref())).await
+}
+
+pub fn inventory(
+    dir: &FnmDir,
+    version: Option<&str>,
+) -> Result<Vec<VersionGlobalPackages>, FnmGuiError> {
+    let versions = match version {
+        Some(version) => {
+            let version = format!("v{}", version.trim().trim_start_matches('v'));
+            if !dir.is_installed(&version) {
+                return Err(FnmGuiError::VersionNotInstalled(version));
+            }
+            vec![version]
+        }
+        None => dir.installed_versions(),
+    };
+
+    Ok(versions
+        .into_iter()
+        .map(|version| VersionGlobalPackages {
+            packages: global_packages(&dir.installation_dir(&version)),
+            version,
+        })
+        .collect())
+}
+
+/// 全局 npm 包的安装目录
+pub fn global_modules_dir(installation: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        installation.join("node_modules")
+    } else {
+        installation.join("lib").join("node_modules")
+    }
+}
+
+/// 全局 bin 所在目录，npm 与 node 在同一目录
+pub fn global_bin_dir(installation: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        installation.to_path_buf()
+    } else {
+        installation.join("bin")
+    }
+}
+
+/// 读取 node_modules 下各个包的 package.json，按包名排列
+pub fn global_packages(installation: &Path) -> Vec<GlobalPackage> {
+    let modules = global_modules_dir(installation);
+    let Ok(entries) = fs::read_dir(&modules) else {
+        return Vec::new();
+    };
+
+    let mut package_dirs = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            // scope 目录下的每个子目录是一个包
+            if let Ok(scoped) = fs::read_dir(entry.path()) {
+                for package in scoped.flatten() {
+                    let package_name =
+                        format!("{}/{}", name, package.file_name().to_string_lossy());
+                    package_dirs.push((package_name, package.path()));
+                }
+            }
+        } else {
+            package_dirs.push((name, entry.path()));
+        }
+    }
+
+    let mut packages: Vec<GlobalPackage> = package_dirs
+        .into_iter()
+        .map(|(name, path)| read_package(name, &path))
+        .collect();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+fn read_package(name: String, path: &Path) -> GlobalPackage {
+    let manifest: Option<serde_json::Value> = fs::read_to_string(path.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    GlobalPackage {
+        version: manifest
+            .as_ref()
+            .and_then(|m| m.get("version")?.as_str().map(|v| v.to_string())),
+        bundled: BUNDLED_PACKAGES.contains(&name.as_str()),
+        linked: fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()),
+        bins: manifest
+            .as_ref()
+            .and_then(|m| m.get("bin"))
+            .map(|bin| package_bins(&name, bin))
+            .unwrap_or_default(),
+        name,
+    }
+}
+
+/// bin 字段为字符串时命令名与包名（不含 scope）相同，为对象时取各个键
+fn package_bins(name: &str, bin: &serde_json::Value) -> Vec<String> {
+    match bin {
+        serde_json::Value::String(_) => {
+            vec![name.rsplit('/').next().unwrap_or(name).to_string()]
+        }
+        serde_json::Value::Object(map) => {
+            let mut bins: Vec<String> = map.keys().cloned().collect();
+            bins.sort();
+            bins
+        }
+        _ => Vec::new(),
+    }
+}
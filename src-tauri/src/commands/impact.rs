@@ -0,0 +1,169 @@
+use super::disk_usage::dir_size;
+use super::fnm_layout::FnmDir;
+use super::processes::{node_processes, NodeProcess};
+use super::project_version::{read_version_spec, resolve_installed};
+use super::projects::{ProjectEntry, ProjectRegistry};
+use super::runner::run_blocking;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{command, State};
+
+/// 卸载前需要用户确认的情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UninstallRisk {
+    /// default 别名指向该版本
+    DefaultVersion,
+    /// 有其他别名指向该版本
+    Aliases,
+    /// 有已记录的项目解析到该版本
+    Projects,
+    /// 有正在运行的 node 进程来自该版本
+    RunningProcesses,
+}
+
+/// 声明的版本解析到待卸载版本的项目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectReference {
+    pub path: String,
+    /// 声明版本的文件
+    pub file: String,
+    pub spec: String,
+}
+
+/// 卸载某个版本的影响
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallImpact {
+    /// 实际会被卸载的版本，如 v20.12.2
+    pub version: String,
+    pub install_path: String,
+    pub is_default: bool,
+    /// 指向该版本的别名（不含 default）
+    pub aliases: Vec<String>,
+    pub projects: Vec<ProjectReference>,
+    pub processes: Vec<NodeProcess>,
+    /// 卸载后可释放的空间（字节）
+    pub reclaimable_bytes: u64,
+    pub risks: Vec<UninstallRisk>,
+    /// 存在任意风险时，卸载需要确认
+    pub requires_confirmation: bool,
+}
+
+impl UninstallImpact {
+    /// 面向用户的风险说明
+    pub fn reasons(&self) -> Vec<String> {
+        self.risks
+            .iter()
+            .map(|risk| match risk {
+                UninstallRisk::DefaultVersion => "该版本是默认版本".to_string(),
+                UninstallRisk::Aliases => format!("别名 {} 指向该版本", self.aliases.join("、")),
+                UninstallRisk::Projects => format!("{} 个项目使用该版本", self.projects.len()),
+                UninstallRisk::RunningProcesses => {
+                    format!("{} 个 node 进程正在运行", self.processes.len())
+                }
+            })
+            .collect()
+    }
+}
+
+/// 分析卸载指定版本的影响
+#[command]
+pub async fn analyze_uninstall(
+    projects: State<'_, ProjectRegistry>,
+    version: String,
+) -> Result<UninstallImpact, FnmGuiError> {
+    let projects = projects.list();
+    run_blocking(move || {
+        analyze(&FnmDir::current()?, &version, &projects)
+            .ok_or(FnmGuiError::VersionNotInstalled(version))
+    })
+    .await
+}
+
+/// 按 fnm 的规则把 version 解析为已安装版本后分析影响，未安装时返回 None
+pub fn analyze(dir: &FnmDir, version: &str, projects: &[ProjectEntry]) -> Option<UninstallImpact> {
+    let version = resolve_installed(version, dir)?;
+    let installation = dir.installation_dir(&version);
+
+    let mut is_default = false;
+    let mut aliases = Vec::new();
+    for alias in dir.aliases() {
+        if alias.dangling || alias.version.as_deref() != Some(version.as_str()) {
+            continue;
+        }
+        if alias.name == "default" {
+            is_default = true;
+        } else {
+            aliases.push(alias.name);
+        }
+    }
+
+    let projects: Vec<ProjectReference> = projects
+        .iter()
+        .filter_map(|project| {
+            let source = read_version_spec(Path::new(&project.path))?;
+            (resolve_installed(&source.spec, dir).as_deref() == Some(version.as_str())).then(|| {
+                ProjectReference {
+                    path: project.path.clone(),
+                    file: source.file,
+                    spec: source.spec,
+                }
+            })
+        })
+        .collect();
+
+    let processes: Vec<NodeProcess> = node_processes(dir)
+        .into_iter()
+        .filter(|process| process.version == version)
+        .collect();
+
+    let mut risks = Vec::new();
+    if is_default {
+        risks.push(UninstallRisk::DefaultVersion);
+    }
+    if !aliases.is_empty() {
+        risks.push(UninstallRisk::Aliases);
+    }
+    if !projects.is_empty() {
+        risks.push(UninstallRisk::Projects);
+    }
+    if !processes.is_empty() {
+        risks.push(UninstallRisk::RunningProcesses);
+    }
+
+    Some(UninstallImpact {
+        reclaimable_bytes: dir_size(&dir.node_versions_dir().join(&version)),
+        install_path: installation.to_string_lossy().to_string(),
+        version,
+        is_default,
+        aliases,
+        projects,
+        processes,
+        requires_confirmation: !risks.is_empty(),
+        risks,
+    })
+}
+
+/// 卸载前的检查：有风险且未确认时返回 ConfirmationRequired
+/// 无法解析为已安装版本时交给 fnm 报告错误
+pub fn check_uninstall(
+    dir: &FnmDir,
+    version: &str,
+    projects: &[ProjectEntry],
+    confirmed: bool,
+) -> Result<(), FnmGuiError> {
+    if confirmed {
+        return Ok(());
+    }
+
+    match analyze(dir, version, projects) {
+        Some(impact) if impact.requires_confirmation => Err(FnmGuiError::ConfirmationRequired {
+            operation: format!("卸载 {}", impact.version),
+            reasons: impact.reasons(),
+        }),
+        _ => Ok(()),
+    }
+}
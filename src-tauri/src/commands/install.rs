@@ -0,0 +1,301 @@
+use super::jobs::{observer_for, run_job, JobKind, JobManager, JobObserver, JobRecord, JobStatus};
+use super::runner::ProcessRunner;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Emitter, State};
+
+/// 安装进度事件名
+pub const INSTALL_PROGRESS_EVENT: &str = "install-progress";
+
+/// 安装阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallPhase {
+    /// 等待同一 fnm 目录上的其他操作完成
+    Queued,
+    Downloading,
+    Extracting,
+    Installing,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 安装进度事件，对应前端的 InstallProgress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallProgress {
+    pub job_id: String,
+    pub version: String,
+    pub status: InstallPhase,
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+}
+
+/// 接收安装进度的回调
+pub type ProgressSink = Arc<dyn Fn(InstallProgress) + Send + Sync>;
+
+/// 安装指定版本
+/// 安装作为任务执行，过程中通过 install-progress 事件推送 fnm 的输出和阶段，任务完成后返回
+#[command]
+pub async fn install_version(
+    app: AppHandle,
+    jobs: State<'_, JobManager>,
+    version: String,
+) -> Result<String, FnmGuiError> {
+    let observer = observer_for(&app, JobKind::Install, &version).await;
+    run_job(
+        &ProcessRunner,
+        jobs.inner(),
+        JobKind::Install,
+        &version,
+        observer.as_ref(),
+    )
+    .await?;
+    Ok(format!("成功安装 {}", version))
+}
+
+/// 取消正在进行的安装任务，终止 fnm 进程并清理未完成的版本目录
+/// 与 cancel_job 相同，保留给按安装任务 ID 取消的调用方
+#[command]
+pub fn cancel_install(jobs: State<'_, JobManager>, job_id: String) -> Result<(), FnmGuiError> {
+    jobs.cancel(&job_id)
+}
+
+/// node-versions 下已有的目录名
+fn existing_version_dirs(fnm_dir: Option<&PathBuf>) -> Vec<String> {
+    let Some(Ok(entries)) = fnm_dir.map(|dir| std::fs::read_dir(dir.join("node-versions"))) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+        .collect()
+}
+
+/// 删除被中断的安装留下的 node-versions/<v> 目录，安装开始前已存在的目录不是本次安装创建的，不会删除
+fn cleanup_partial_install(fnm_dir: Option<&PathBuf>, version: &str, existing: &[String]) {
+    let fnm_dir = match fnm_dir {
+        Some(dir) if !version.is_empty() => dir,
+        _ => return,
+    };
+    if existing.iter().any(|dir| dir == version) {
+        return;
+    }
+
+    let version_dir = fnm_dir.join("node-versions").join(version);
+    if version_dir.exists() {
+        let _ = std::fs::remove_dir_all(&version_dir);
+    }
+}
+
+/// 将安装任务的状态和 fnm 输出转换为进度事件，取消时清理未完成的版本目录
+pub struct ProgressReporter {
+    sink: ProgressSink,
+    version: String,
+    fnm_dir: Option<PathBuf>,
+    state: Mutex<ReporterState>,
+}
+
+#[derive(Default)]
+struct ReporterState {
+    job_id: String,
+    phase: Option<InstallPhase>,
+    /// 从 "Installing Node v20.12.2 (x64)" 中解析出的实际版本
+    resolved_version: Option<String>,
+    /// 任务开始运行、启动 fnm 前 node-versions 下已有的目录；仍在排队的任务为 None
+    existing_dirs: Option<Vec<String>>,
+}
+
+impl ProgressReporter {
+    pub fn new(sink: ProgressSink, version: &str, fnm_dir: Option<PathBuf>) -> Self {
+        ProgressReporter {
+            sink,
+            version: version.to_string(),
+            fnm_dir,
+            state: Mutex::new(ReporterState::default()),
+        }
+    }
+
+    /// 通过 install-progress 事件推送进度
+    pub fn for_app(app: AppHandle, version: &str, fnm_dir: Option<PathBuf>) -> Self {
+        let sink: ProgressSink = Arc::new(move |progress| {
+            let _ = app.emit(INSTALL_PROGRESS_EVENT, progress);
+        });
+        Self::new(sink, version, fnm_dir)
+    }
+
+    fn emit(&self, status: InstallPhase, progress: Option<f64>, message: Option<String>) {
+        let job_id = self.state.lock().unwrap().job_id.clone();
+        (self.sink)(InstallProgress {
+            job_id,
+            version: self.version.clone(),
+            status,
+            progress,
+            message,
+        });
+    }
+
+    /// 实际安装的版本目录名，未解析到时回退为用户输入的完整版本号
+    fn installed_version(&self) -> String {
+        if let Some(ref resolved) = self.state.lock().unwrap().resolved_version {
+            return resolved.clone();
+        }
+
+        let version = self.version.trim_start_matches('v');
+        if version.split('.').count() == 3 && version.split('.').all(|p| p.parse::<u64>().is_ok()) {
+            format!("v{}", version)
+        } else {
+            String::new()
+        }
+    }
+}
+
+impl JobObserver for ProgressReporter {
+    fn on_update(&self, job: &JobRecord) {
+        self.state.lock().unwrap().job_id = job.id.clone();
+
+        match job.status {
+            JobStatus::Queued => self.emit(
+                InstallPhase::Queued,
+                None,
+                Some("等待其他操作完成".to_string()),
+            ),
+            JobStatus::Running => {
+                let existing = existing_version_dirs(self.fnm_dir.as_ref());
+                let mut state = self.state.lock().unwrap();
+                state.phase = Some(InstallPhase::Downloading);
+                state.existing_dirs = Some(existing);
+                drop(state);
+                self.emit(InstallPhase::Downloading, None, None);
+            }
+            JobStatus::Succeeded => self.emit(InstallPhase::Completed, Some(100.0), None),
+            JobStatus::Failed | JobStatus::Interrupted => {
+                self.emit(InstallPhase::Failed, None, job.error.clone())
+            }
+            JobStatus::Cancelled => {
+                // 排队期间取消时 fnm 还没有启动，没有需要清理的目录
+                let existing = self.state.lock().unwrap().existing_dirs.clone();
+                if let Some(existing) = existing {
+                    cleanup_partial_install(
+                        self.fnm_dir.as_ref(),
+                        &self.installed_version(),
+                        &existing,
+                    );
+                }
+                self.emit(
+                    InstallPhase::Cancelled,
+                    None,
+                    Some("安装已取消".to_string()),
+                );
+            }
+        }
+    }
+
+    fn on_line(&self, line: &str) {
+        let phase = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(resolved) = parse_installing_version(line) {
+                state.resolved_version = Some(resolved);
+            }
+            if let Some(next) = detect_phase(line) {
+                state.phase = Some(next);
+            }
+            state.phase.unwrap_or(InstallPhase::Downloading)
+        };
+
+        self.emit(phase, parse_percentage(line), Some(line.to_string()));
+    }
+}
+
+/// 根据 fnm 输出内容推断当前阶段
+fn detect_phase(line: &str) -> Option<InstallPhase> {
+    let line = line.to_lowercase();
+
+    if line.contains("extract") {
+        Some(InstallPhase::Extracting)
+    } else if line.contains("download") || line.starts_with("installing node") {
+        // fnm 在开始下载前输出 "Installing Node vX (arch)"
+        Some(InstallPhase::Downloading)
+    } else if line.contains("install") || line.contains("using node") || line.contains("alias") {
+        Some(InstallPhase::Installing)
+    } else {
+        None
+    }
+}
+
+/// 解析 "Installing Node v20.12.2 (x64)" 中的版本号
+fn parse_installing_version(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("Installing Node ")?;
+    let version = rest.split_whitespace().next()?;
+    version.starts_with('v').then(|| version.to_string())
+}
+
+/// 解析行内的百分比，如 "45%"
+fn parse_percentage(line: &str) -> Option<f64> {
+    line.split_whitespace()
+        .filter_map(|word| word.strip_suffix('%'))
+        .find_map(|number| number.parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::jobs::{JobKind, JobManager};
+
+    fn reporter(fnm_dir: &std::path::Path, version: &str) -> ProgressReporter {
+        ProgressReporter::new(Arc::new(|_| {}), version, Some(fnm_dir.to_path_buf()))
+    }
+
+    fn report(reporter: &ProgressReporter, version: &str, statuses: &[JobStatus]) {
+        let (mut job, _cancel_rx) = JobManager::default().create(JobKind::Install, version, None);
+        for &status in statuses {
+            job.status = status;
+            reporter.on_update(&job);
+        }
+    }
+
+    #[test]
+    fn cancel_keeps_versions_that_existed_before_the_install() {
+        let fnm_dir = tempfile::tempdir().unwrap();
+        let installation = fnm_dir.path().join("node-versions/v20.12.2/installation");
+        std::fs::create_dir_all(&installation).unwrap();
+
+        let reporter = reporter(fnm_dir.path(), "20.12.2");
+        report(&reporter, "20.12.2", &[JobStatus::Running]);
+        reporter.on_line("Installing Node v20.12.2 (x64)");
+        report(&reporter, "20.12.2", &[JobStatus::Cancelled]);
+
+        assert!(installation.is_dir());
+    }
+
+    #[test]
+    fn cancel_removes_the_directory_created_by_the_install() {
+        let fnm_dir = tempfile::tempdir().unwrap();
+        let reporter = reporter(fnm_dir.path(), "22");
+        report(&reporter, "22", &[JobStatus::Running]);
+
+        reporter.on_line("Installing Node v22.11.0 (x64)");
+        let created = fnm_dir.path().join("node-versions/v22.11.0");
+        std::fs::create_dir_all(created.join("installation")).unwrap();
+        report(&reporter, "22", &[JobStatus::Cancelled]);
+
+        assert!(!created.exists());
+    }
+
+    #[test]
+    fn cancel_while_queued_leaves_the_version_directory_alone() {
+        let fnm_dir = tempfile::tempdir().unwrap();
+        let installation = fnm_dir.path().join("node-versions/v22.11.0/installation");
+
+        let reporter = reporter(fnm_dir.path(), "22.11.0");
+        report(&reporter, "22.11.0", &[JobStatus::Queued]);
+        std::fs::create_dir_all(&installation).unwrap();
+        report(&reporter, "22.11.0", &[JobStatus::Cancelled]);
+
+        assert!(installation.is_dir());
+    }
+}
@@ -0,0 +1,459 @@
+use super::fnm_dir::fnm_dir_path;
+use super::install::ProgressReporter;
+use super::operation::{lock_fnm_dir, Operation, OperationKind};
+use super::runner::{describe, run_blocking, FnmRunner, ProcessRunner};
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{command, AppHandle, Manager, State};
+use tokio::sync::oneshot;
+
+/// 任务状态变化事件名
+pub const JOB_UPDATED_EVENT: &str = "job-updated";
+
+/// 任务历史文件名，位于应用数据目录下
+const JOBS_FILE: &str = "jobs.json";
+
+/// 最多保留的历史任务数
+const MAX_HISTORY: usize = 200;
+
+/// 每个任务最多保留的输出长度（字节），超出时丢弃最早的内容
+const MAX_OUTPUT_LEN: usize = 64 * 1024;
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    Install,
+    Uninstall,
+}
+
+impl JobKind {
+    fn subcommand(self) -> &'static str {
+        match self {
+            JobKind::Install => "install",
+            JobKind::Uninstall => "uninstall",
+        }
+    }
+
+    fn operation_kind(self) -> OperationKind {
+        match self {
+            JobKind::Install => OperationKind::Install,
+            JobKind::Uninstall => OperationKind::Uninstall,
+        }
+    }
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    /// 等待同一 fnm 目录上的其他操作完成
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    /// 应用退出时仍未完成
+    Interrupted,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+/// 一次 fnm 任务的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: JobKind,
+    pub version: String,
+    /// 传给 fnm 的参数，如 ["install", "20"]
+    pub args: Vec<String>,
+    pub status: JobStatus,
+    /// 时间均为 Unix 毫秒时间戳
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub ended_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// fnm 的 stdout 和 stderr，按输出顺序合并
+    pub output: String,
+    pub error: Option<String>,
+    /// 由哪个任务重试而来
+    pub retry_of: Option<String>,
+}
+
+/// 任务执行过程中的回调
+pub trait JobObserver: Send + Sync {
+    /// 任务状态变化
+    fn on_update(&self, _job: &JobRecord) {}
+
+    /// fnm 输出的每一行
+    fn on_line(&self, _line: &str) {}
+}
+
+impl JobObserver for () {}
+
+type JobNotifier = Box<dyn Fn(&JobRecord) + Send + Sync>;
+
+/// 任务管理器，记录所有任务并持久化到应用数据目录
+#[derive(Default)]
+pub struct JobManager {
+    state: Mutex<JobState>,
+    notifier: Option<JobNotifier>,
+}
+
+#[derive(Default)]
+struct JobState {
+    path: Option<PathBuf>,
+    /// 按创建时间排列，最新的在最后
+    records: Vec<JobRecord>,
+    /// 未结束任务的取消信号发送端
+    cancels: HashMap<String, oneshot::Sender<()>>,
+}
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+impl JobManager {
+    /// 读取历史任务，上次退出时未完成的任务标记为 Interrupted
+    pub fn load(data_dir: PathBuf) -> Self {
+        let path = data_dir.join(JOBS_FILE);
+        let mut records: Vec<JobRecord> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        for record in records.iter_mut().filter(|r| !r.status.is_finished()) {
+            record.status = JobStatus::Interrupted;
+        }
+
+        JobManager {
+            state: Mutex::new(JobState {
+                path: Some(path),
+                records,
+                cancels: HashMap::new(),
+            }),
+            notifier: None,
+        }
+    }
+
+    /// 任务状态变化时调用 notifier，如推送 job-updated 事件
+    pub fn with_notifier(mut self, notifier: impl Fn(&JobRecord) + Send + Sync + 'static) -> Self {
+        self.notifier = Some(Box::new(notifier));
+        self
+    }
+
+    /// 全部任务，最新的在前
+    pub fn list(&self) -> Vec<JobRecord> {
+        self.state
+            .lock()
+            .unwrap()
+            .records
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, job_id: &str) -> Result<JobRecord, FnmGuiError> {
+        self.state
+            .lock()
+            .unwrap()
+            .records
+            .iter()
+            .find(|r| r.id == job_id)
+            .cloned()
+            .ok_or_else(|| FnmGuiError::JobNotFound(job_id.to_string()))
+    }
+
+    /// 取消排队中或执行中的任务
+    pub fn cancel(&self, job_id: &str) -> Result<(), FnmGuiError> {
+        let cancel_tx = {
+            let mut state = self.state.lock().unwrap();
+            let record = state
+                .records
+                .iter()
+                .find(|r| r.id == job_id)
+                .ok_or_else(|| FnmGuiError::JobNotFound(job_id.to_string()))?;
+            if record.status.is_finished() {
+                return Err(FnmGuiError::InvalidInput(format!("任务 {} 已结束", job_id)));
+            }
+            state.cancels.remove(job_id)
+        };
+
+        // 发送失败说明任务恰好已经结束
+        match cancel_tx.map(|tx| tx.send(())) {
+            Some(Ok(())) => Ok(()),
+            _ => Err(FnmGuiError::InvalidInput(format!("任务 {} 已结束", job_id))),
+        }
+    }
+
+    /// 创建排队中的任务，返回任务记录和取消信号的接收端
+    pub fn create(
+        &self,
+        kind: JobKind,
+        version: &str,
+        retry_of: Option<String>,
+    ) -> (JobRecord, oneshot::Receiver<()>) {
+        let created_at = now_millis();
+        let record = JobRecord {
+            id: format!(
+                "{}-{}-{}",
+                kind.subcommand(),
+                created_at,
+                NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed)
+            ),
+            kind,
+            version: version.to_string(),
+            args: vec![kind.subcommand().to_string(), version.to_string()],
+            status: JobStatus::Queued,
+            created_at,
+            started_at: None,
+            ended_at: None,
+            exit_code: None,
+            output: String::new(),
+            error: None,
+            retry_of,
+        };
+
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.cancels.insert(record.id.clone(), cancel_tx);
+            state.records.push(record.clone());
+            // 超出上限时丢弃最早的已结束任务
+            while state.records.len() > MAX_HISTORY {
+                match state.records.iter().position(|r| r.status.is_finished()) {
+                    Some(index) => state.records.remove(index),
+                    None => break,
+                };
+            }
+            persist(&state);
+        }
+        self.notify(&record);
+
+        (record, cancel_rx)
+    }
+
+    /// 修改任务记录，保存并通知状态变化
+    fn update(&self, job_id: &str, f: impl FnOnce(&mut JobRecord)) -> Option<JobRecord> {
+        let record = {
+            let mut state = self.state.lock().unwrap();
+            let record = state.records.iter_mut().find(|r| r.id == job_id)?;
+            f(record);
+            let record = record.clone();
+            if record.status.is_finished() {
+                state.cancels.remove(job_id);
+            }
+            persist(&state);
+            record
+        };
+        self.notify(&record);
+        Some(record)
+    }
+
+    /// 追加一行输出，只保存在内存中，任务结束时随状态一起写入文件
+    fn append_output(&self, job_id: &str, line: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(record) = state.records.iter_mut().find(|r| r.id == job_id) {
+            record.output.push_str(line);
+            record.output.push('\n');
+            if record.output.len() > MAX_OUTPUT_LEN {
+                let excess = record.output.len() - MAX_OUTPUT_LEN;
+                let cut = record.output[excess..]
+                    .find('\n')
+                    .map_or(record.output.len(), |i| excess + i + 1);
+                record.output.drain(..cut);
+            }
+        }
+    }
+
+    fn notify(&self, record: &JobRecord) {
+        if let Some(ref notifier) = self.notifier {
+            notifier(record);
+        }
+    }
+}
+
+/// 写入任务历史文件，失败时忽略（历史记录不影响任务本身）
+fn persist(state: &JobState) {
+    let Some(ref path) = state.path else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(content) = serde_json::to_string_pretty(&state.records) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// 创建任务并执行，等待任务结束
+pub async fn run_job(
+    runner: &dyn FnmRunner,
+    jobs: &JobManager,
+    kind: JobKind,
+    version: &str,
+    observer: &dyn JobObserver,
+) -> Result<JobRecord, FnmGuiError> {
+    let (job, cancel_rx) = jobs.create(kind, version, None);
+    execute_job(runner, jobs, job, cancel_rx, observer).await
+}
+
+/// 执行已创建的任务：获取 fnm 目录的操作锁后运行 fnm，期间可通过 cancel_rx 取消
+pub async fn execute_job(
+    runner: &dyn FnmRunner,
+    jobs: &JobManager,
+    job: JobRecord,
+    mut cancel_rx: oneshot::Receiver<()>,
+    observer: &dyn JobObserver,
+) -> Result<JobRecord, FnmGuiError> {
+    let id = job.id.clone();
+    let command = describe(&[job.kind.subcommand()]);
+    observer.on_update(&job);
+
+    let finish = |status: JobStatus, exit_code: Option<i32>, error: Option<&FnmGuiError>| {
+        let updated = jobs.update(&id, |record| {
+            record.status = status;
+            record.ended_at = Some(now_millis());
+            record.exit_code = exit_code;
+            record.error = error.map(|e| e.to_string());
+        });
+        if let Some(ref record) = updated {
+            observer.on_update(record);
+        }
+    };
+    // 同一版本上已有操作时直接失败，其他修改操作完成后再开始；排队期间也可以取消
+    let operation = Operation::new(job.kind.operation_kind(), &job.version);
+    let label = operation.describe();
+    let cancelled = || {
+        finish(JobStatus::Cancelled, None, None);
+        FnmGuiError::Cancelled(label.clone())
+    };
+
+    let locked = match runner.fnm_dir() {
+        Ok(fnm_dir) => tokio::select! {
+            guard = lock_fnm_dir(fnm_dir, operation) => Some(guard),
+            _ = &mut cancel_rx => None,
+        },
+        Err(e) => Some(Err(e)),
+    };
+    let _guard = match locked {
+        Some(Ok(guard)) => guard,
+        Some(Err(e)) => {
+            finish(JobStatus::Failed, None, Some(&e));
+            return Err(e);
+        }
+        None => return Err(cancelled()),
+    };
+
+    if let Some(record) = jobs.update(&id, |record| {
+        record.status = JobStatus::Running;
+        record.started_at = Some(now_millis());
+    }) {
+        observer.on_update(&record);
+    }
+
+    let args: Vec<&str> = job.args.iter().map(String::as_str).collect();
+    let on_line = |line: &str| {
+        jobs.append_output(&id, line);
+        observer.on_line(line);
+    };
+
+    let output = tokio::select! {
+        result = runner.stream(&args, &on_line) => match result {
+            Ok(output) => output,
+            Err(e) => {
+                finish(JobStatus::Failed, None, Some(&e));
+                return Err(e);
+            }
+        },
+        _ = &mut cancel_rx => return Err(cancelled()),
+    };
+
+    if !output.is_success() {
+        let e = FnmGuiError::from_output(command, Some(&job.version), &output);
+        finish(JobStatus::Failed, output.code, Some(&e));
+        return Err(e);
+    }
+
+    finish(JobStatus::Succeeded, output.code, None);
+    jobs.get(&id)
+}
+
+/// 获取任务历史，最新的在前
+#[command]
+pub fn list_jobs(jobs: State<'_, JobManager>) -> Result<Vec<JobRecord>, FnmGuiError> {
+    Ok(jobs.list())
+}
+
+/// 获取单个任务，包括执行中任务当前的输出
+#[command]
+pub fn get_job(jobs: State<'_, JobManager>, job_id: String) -> Result<JobRecord, FnmGuiError> {
+    jobs.get(&job_id)
+}
+
+/// 取消排队中或执行中的任务
+#[command]
+pub fn cancel_job(jobs: State<'_, JobManager>, job_id: String) -> Result<(), FnmGuiError> {
+    jobs.cancel(&job_id)
+}
+
+/// 以相同参数重新执行已结束的任务，立即返回新任务，任务在后台执行
+#[command]
+pub async fn retry_job(
+    app: AppHandle,
+    jobs: State<'_, JobManager>,
+    job_id: String,
+) -> Result<JobRecord, FnmGuiError> {
+    let previous = jobs.get(&job_id)?;
+    if !previous.status.is_finished() {
+        return Err(FnmGuiError::InvalidInput(format!(
+            "任务 {} 仍在进行中",
+            job_id
+        )));
+    }
+
+    let observer = observer_for(&app, previous.kind, &previous.version).await;
+    let (job, cancel_rx) = jobs.create(previous.kind, &previous.version, Some(job_id));
+
+    let record = job.clone();
+    tauri::async_runtime::spawn(async move {
+        let jobs = app.state::<JobManager>();
+        let _ = execute_job(
+            &ProcessRunner,
+            jobs.inner(),
+            job,
+            cancel_rx,
+            observer.as_ref(),
+        )
+        .await;
+    });
+
+    Ok(record)
+}
+
+/// 任务对应的回调：安装任务需要推送 install-progress 事件
+pub async fn observer_for(app: &AppHandle, kind: JobKind, version: &str) -> Box<dyn JobObserver> {
+    match kind {
+        JobKind::Install => {
+            let fnm_dir = run_blocking(fnm_dir_path).await.ok();
+            Box::new(ProgressReporter::for_app(app.clone(), version, fnm_dir))
+        }
+        JobKind::Uninstall => Box::new(()),
+    }
+}
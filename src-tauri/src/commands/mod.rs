@@ -1,4 +1,25 @@
+pub mod alias;
 pub mod common;
+pub mod disk_usage;
 pub mod env;
+pub mod fnm_dir;
+pub mod fnm_layout;
 pub mod fs;
+pub mod global_packages;
+pub mod impact;
+pub mod install;
+pub mod jobs;
+pub mod operation;
+pub mod package_json;
+pub mod processes;
+pub mod project_version;
+pub mod projects;
+pub mod prune;
+pub mod remote;
+pub mod runner;
+pub mod semver;
+pub mod settings;
+pub mod shell_env;
+pub mod shell_profile;
+pub mod upgrade;
 pub mod version;
@@ -0,0 +1,157 @@
+use super::fnm_dir::fnm_dir_path;
+use super::runner::run_blocking;
+use crate::error::FnmGuiError;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+/// 会修改 fnm 目录的操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Install,
+    Uninstall,
+    Use,
+    SetDefault,
+    Alias,
+    Corepack,
+}
+
+impl OperationKind {
+    fn label(self) -> &'static str {
+        match self {
+            OperationKind::Install => "安装",
+            OperationKind::Uninstall => "卸载",
+            OperationKind::Use => "切换到",
+            OperationKind::SetDefault => "设置默认版本",
+            OperationKind::Alias => "修改别名",
+            OperationKind::Corepack => "切换 Corepack",
+        }
+    }
+}
+
+/// 一次修改操作及其涉及的版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub kind: OperationKind,
+    pub version: Option<String>,
+}
+
+impl Operation {
+    pub fn new(kind: OperationKind, version: &str) -> Self {
+        Operation {
+            kind,
+            version: Some(version.to_string()),
+        }
+    }
+
+    /// 不针对特定版本的操作
+    pub fn global(kind: OperationKind) -> Self {
+        Operation {
+            kind,
+            version: None,
+        }
+    }
+
+    /// 如 "安装 v20"
+    pub fn describe(&self) -> String {
+        match self.version {
+            Some(ref version) => format!("{} {}", self.kind.label(), version),
+            None => self.kind.label().to_string(),
+        }
+    }
+
+    /// 两个操作涉及同一版本时互相冲突，如 "20" 与 "v20.12.2"
+    fn conflicts_with(&self, other: &Operation) -> bool {
+        let (Some(a), Some(b)) = (&self.version, &other.version) else {
+            return false;
+        };
+
+        let a = a.trim().trim_start_matches('v');
+        let b = b.trim().trim_start_matches('v');
+        let is_prefix =
+            |short: &str, long: &str| long == short || long.starts_with(&format!("{}.", short));
+        is_prefix(a, b) || is_prefix(b, a)
+    }
+}
+
+/// 某个 fnm 目录上正在执行和排队中的操作
+struct DirOperations {
+    queue: Arc<tokio::sync::Mutex<()>>,
+    operations: Vec<(u64, Operation)>,
+}
+
+/// 按 fnm 目录记录的操作，同一目录上的修改操作依次执行
+static OPERATIONS: Mutex<BTreeMap<PathBuf, DirOperations>> = Mutex::new(BTreeMap::new());
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 持有期间独占 fnm 目录，释放时让下一个排队的操作继续
+/// 字段按声明顺序 drop：先释放排队锁，再移除登记，避免目录记录被提前清除
+pub struct OperationGuard {
+    _permit: OwnedMutexGuard<()>,
+    _registration: Registration,
+}
+
+/// 登记在 OPERATIONS 中的操作，drop 时移除（包括排队中被取消的情况）
+struct Registration {
+    fnm_dir: PathBuf,
+    id: u64,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut dirs = OPERATIONS.lock().unwrap();
+        if let Some(dir) = dirs.get_mut(&self.fnm_dir) {
+            dir.operations.retain(|(id, _)| *id != self.id);
+            if dir.operations.is_empty() {
+                dirs.remove(&self.fnm_dir);
+            }
+        }
+    }
+}
+
+/// 获取 fnm 目录的操作锁
+/// 与正在执行或排队中的操作涉及同一版本时直接拒绝，否则排队等待前面的操作完成
+pub async fn lock_fnm_dir(
+    fnm_dir: PathBuf,
+    operation: Operation,
+) -> Result<OperationGuard, FnmGuiError> {
+    let (registration, queue) = {
+        let mut dirs = OPERATIONS.lock().unwrap();
+        let dir = dirs
+            .entry(fnm_dir.clone())
+            .or_insert_with(|| DirOperations {
+                queue: Arc::new(tokio::sync::Mutex::new(())),
+                operations: Vec::new(),
+            });
+
+        if let Some((_, running)) = dir
+            .operations
+            .iter()
+            .find(|(_, other)| operation.conflicts_with(other))
+        {
+            return Err(FnmGuiError::OperationConflict {
+                operation: operation.describe(),
+                running: running.describe(),
+            });
+        }
+
+        let id = NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed);
+        dir.operations.push((id, operation));
+        (Registration { fnm_dir, id }, dir.queue.clone())
+    };
+
+    let permit = queue.lock_owned().await;
+    Ok(OperationGuard {
+        _permit: permit,
+        _registration: registration,
+    })
+}
+
+/// 获取当前 fnm 目录的操作锁
+pub async fn lock_current_fnm_dir(operation: Operation) -> Result<OperationGuard, FnmGuiError> {
+    let fnm_dir = run_blocking(fnm_dir_path).await?;
+    lock_fnm_dir(fnm_dir, operation).await
+}
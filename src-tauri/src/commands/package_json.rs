@@ -0,0 +1,381 @@
+use crate::error::FnmGuiError;
+
+/// JSON 对象中的一个成员在原文中的位置
+struct Member {
+    key: String,
+    /// 键的起始位置（引号处）
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+/// 在 package.json 原文中设置 outer.inner 的字符串值，只改动该值所在的位置
+/// 保留原有的缩进、换行符（LF 或 CRLF）和键的顺序，缺少的键追加在对象末尾
+pub fn set_nested_string(
+    text: &str,
+    outer: &str,
+    inner: &str,
+    value: &str,
+) -> Result<String, FnmGuiError> {
+    // 先完整解析一遍，确保后续按位置修改时面对的是合法的 JSON
+    let parsed: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| FnmGuiError::parse("package.json", e))?;
+    if !parsed.is_object() {
+        return Err(invalid("package.json 的顶层不是对象"));
+    }
+
+    let bytes = text.as_bytes();
+    let root = skip_whitespace(bytes, 0);
+    let indent_unit = indent_unit(text, root);
+    let encoded = serde_json::to_string(value).map_err(|e| FnmGuiError::parse("版本号", e))?;
+    let members = object_members(bytes, root)?;
+
+    let Some(outer_member) = members.iter().find(|m| m.key == outer) else {
+        let member = format!(
+            "{}: {}",
+            json_key(outer),
+            object_literal(text, root, &indent_unit, inner, &encoded)
+        );
+        return Ok(insert_member(text, root, &members, &member));
+    };
+
+    if bytes[outer_member.value_start] != b'{' {
+        return Err(invalid(&format!("package.json 中的 {} 不是对象", outer)));
+    }
+    let inner_members = object_members(bytes, outer_member.value_start)?;
+    match inner_members.iter().find(|m| m.key == inner) {
+        Some(member) => Ok(format!(
+            "{}{}{}",
+            &text[..member.value_start],
+            encoded,
+            &text[member.value_end..]
+        )),
+        None => Ok(insert_member(
+            text,
+            outer_member.value_start,
+            &inner_members,
+            &format!("{}: {}", json_key(inner), encoded),
+        )),
+    }
+}
+
+fn invalid(message: &str) -> FnmGuiError {
+    FnmGuiError::InvalidInput(message.to_string())
+}
+
+fn json_key(key: &str) -> String {
+    serde_json::Value::String(key.to_string()).to_string()
+}
+
+/// 在对象末尾追加成员，沿用最后一个成员的缩进；单行对象保持单行
+fn insert_member(text: &str, object_start: usize, members: &[Member], member: &str) -> String {
+    let close = object_end(text.as_bytes(), object_start) - 1;
+
+    match members.last() {
+        Some(last) => {
+            let indent = leading_indent(text, last.key_start);
+            let separator = if is_multiline(text, object_start, close) {
+                format!(",{}{}", line_ending(text), indent)
+            } else {
+                ", ".to_string()
+            };
+            format!(
+                "{}{}{}{}",
+                &text[..last.value_end],
+                separator,
+                member,
+                &text[last.value_end..]
+            )
+        }
+        None => {
+            let outer_indent = leading_indent(text, object_start);
+            let unit = indent_unit(text, skip_whitespace(text.as_bytes(), 0));
+            let newline = line_ending(text);
+            format!(
+                "{}{{{}{}{}{}{}{}}}{}",
+                &text[..object_start],
+                newline,
+                outer_indent,
+                unit,
+                member,
+                newline,
+                outer_indent,
+                &text[close + 1..]
+            )
+        }
+    }
+}
+
+/// 新建只有一个成员的对象，所在对象为单行时也写成单行
+fn object_literal(text: &str, root: usize, unit: &str, key: &str, encoded: &str) -> String {
+    let bytes = text.as_bytes();
+    let close = object_end(bytes, root) - 1;
+    let empty = object_members(bytes, root).is_ok_and(|members| members.is_empty());
+    if empty || is_multiline(text, root, close) {
+        format!(
+            "{{{3}{0}{0}{1}: {2}{3}{0}}}",
+            unit,
+            json_key(key),
+            encoded,
+            line_ending(text)
+        )
+    } else {
+        format!("{{ {}: {} }}", json_key(key), encoded)
+    }
+}
+
+/// 原文使用 CRLF 时新增的行也使用 CRLF
+fn line_ending(text: &str) -> &'static str {
+    if text.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn is_multiline(text: &str, open: usize, close: usize) -> bool {
+    text[open..close].contains('\n')
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+/// pos 所在行的行首空白
+fn leading_indent(text: &str, pos: usize) -> &str {
+    let start = line_start(text, pos);
+    let line = &text[start..pos];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// 顶层对象第一个成员的缩进，默认两个空格
+fn indent_unit(text: &str, root: usize) -> String {
+    object_members(text.as_bytes(), root)
+        .ok()
+        .and_then(|members| members.first().map(|m| m.key_start))
+        .filter(|&start| is_multiline(text, root, start))
+        .map(|start| leading_indent(text, start).to_string())
+        .filter(|indent| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+/// 列出对象的成员，start 指向左花括号
+fn object_members(bytes: &[u8], start: usize) -> Result<Vec<Member>, FnmGuiError> {
+    let mut members = Vec::new();
+    let mut pos = skip_whitespace(bytes, start + 1);
+
+    while bytes.get(pos) == Some(&b'"') {
+        let key_start = pos;
+        let key_end = skip_string(bytes, pos);
+        let key: String = serde_json::from_slice(&bytes[key_start..key_end])
+            .map_err(|e| FnmGuiError::parse("package.json", e))?;
+        pos = skip_whitespace(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return Err(invalid("package.json 格式不正确"));
+        }
+        let value_start = skip_whitespace(bytes, pos + 1);
+        let value_end = skip_value(bytes, value_start);
+        members.push(Member {
+            key,
+            key_start,
+            value_start,
+            value_end,
+        });
+
+        pos = skip_whitespace(bytes, value_end);
+        if bytes.get(pos) == Some(&b',') {
+            pos = skip_whitespace(bytes, pos + 1);
+        }
+    }
+
+    Ok(members)
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+/// 跳过字符串，返回结束引号之后的位置
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut pos = start + 1;
+    while let Some(&b) = bytes.get(pos) {
+        match b {
+            b'\\' => pos += 2,
+            b'"' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    pos
+}
+
+/// 跳过对象或数组，返回结束括号之后的位置
+fn object_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut pos = start;
+    while let Some(&b) = bytes.get(pos) {
+        match b {
+            b'"' => {
+                pos = skip_string(bytes, pos);
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos + 1;
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    pos
+}
+
+fn skip_value(bytes: &[u8], start: usize) -> usize {
+    match bytes.get(start) {
+        Some(b'"') => skip_string(bytes, start),
+        Some(b'{') | Some(b'[') => object_end(bytes, start),
+        _ => {
+            let mut pos = start;
+            while bytes
+                .get(pos)
+                .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+            {
+                pos += 1;
+            }
+            pos
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(text: &str, outer: &str, inner: &str, value: &str) -> String {
+        set_nested_string(text, outer, inner, value).unwrap()
+    }
+
+    #[test]
+    fn replaces_an_existing_value() {
+        let text = "{\n  \"name\": \"app\",\n  \"engines\": {\n    \"node\": \">=18\",\n    \"npm\": \">=9\"\n  }\n}\n";
+        assert_eq!(
+            set(text, "engines", "node", "20.12.2"),
+            "{\n  \"name\": \"app\",\n  \"engines\": {\n    \"node\": \"20.12.2\",\n    \"npm\": \">=9\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn adds_a_missing_inner_key() {
+        let multi_line =
+            "{\n  \"engines\": {\n    \"npm\": \">=9\"\n  },\n  \"private\": true\n}\n";
+        assert_eq!(
+            set(multi_line, "engines", "node", "20"),
+            "{\n  \"engines\": {\n    \"npm\": \">=9\",\n    \"node\": \"20\"\n  },\n  \"private\": true\n}\n"
+        );
+
+        let single_line = r#"{"name":"app","engines":{ "npm": ">=9" }}"#;
+        assert_eq!(
+            set(single_line, "engines", "node", "20"),
+            r#"{"name":"app","engines":{ "npm": ">=9", "node": "20" }}"#
+        );
+
+        let empty_inner = "{\n  \"volta\": {}\n}";
+        assert_eq!(
+            set(empty_inner, "volta", "node", "20.12.2"),
+            "{\n  \"volta\": {\n    \"node\": \"20.12.2\"\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn adds_a_missing_outer_object() {
+        assert_eq!(
+            set("{\n  \"name\": \"app\"\n}\n", "volta", "node", "20.12.2"),
+            "{\n  \"name\": \"app\",\n  \"volta\": {\n    \"node\": \"20.12.2\"\n  }\n}\n"
+        );
+        assert_eq!(
+            set(r#"{"name":"app"}"#, "engines", "node", ">=20"),
+            r#"{"name":"app", "engines": { "node": ">=20" }}"#
+        );
+    }
+
+    #[test]
+    fn fills_an_empty_object() {
+        assert_eq!(
+            set("{}", "engines", "node", ">=20"),
+            "{\n  \"engines\": {\n    \"node\": \">=20\"\n  }\n}"
+        );
+        assert_eq!(
+            set("{ }\n", "engines", "node", ">=20"),
+            "{\n  \"engines\": {\n    \"node\": \">=20\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn handles_escaped_quotes_and_backslashes() {
+        let text = r#"{"engines":{"a\"b":"c\\d}","node":"18"},"we\"ird":{"k\\ey":"old"}}"#;
+
+        assert_eq!(
+            set(text, "engines", "node", r#"1"2\3"#),
+            r#"{"engines":{"a\"b":"c\\d}","node":"1\"2\\3"},"we\"ird":{"k\\ey":"old"}}"#
+        );
+        assert_eq!(
+            set(text, "we\"ird", "k\\ey", "new"),
+            r#"{"engines":{"a\"b":"c\\d}","node":"18"},"we\"ird":{"k\\ey":"new"}}"#
+        );
+
+        let added = set(text, "we\"ird", "n\"ew", "x\\y");
+        let parsed: serde_json::Value = serde_json::from_str(&added).unwrap();
+        assert_eq!(parsed["we\"ird"]["n\"ew"], "x\\y");
+        assert_eq!(parsed["we\"ird"]["k\\ey"], "old");
+    }
+
+    #[test]
+    fn preserves_key_order_and_indentation() {
+        let text = "{\n\t\"name\": \"app\",\n\t\"engines\": {\n\t\t\"npm\": \">=9\"\n\t},\n\t\"scripts\": {}\n}\n";
+
+        let updated = set(text, "volta", "node", "20.12.2");
+        assert_eq!(
+            updated,
+            "{\n\t\"name\": \"app\",\n\t\"engines\": {\n\t\t\"npm\": \">=9\"\n\t},\n\t\"scripts\": {},\n\t\"volta\": {\n\t\t\"node\": \"20.12.2\"\n\t}\n}\n"
+        );
+        assert_eq!(
+            set(&updated, "engines", "node", ">=20"),
+            "{\n\t\"name\": \"app\",\n\t\"engines\": {\n\t\t\"npm\": \">=9\",\n\t\t\"node\": \">=20\"\n\t},\n\t\"scripts\": {},\n\t\"volta\": {\n\t\t\"node\": \"20.12.2\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let text =
+            "{\r\n  \"name\": \"app\",\r\n  \"engines\": {\r\n    \"npm\": \">=9\"\r\n  }\r\n}\r\n";
+
+        let updated = set(text, "engines", "node", "20");
+        assert_eq!(
+            updated,
+            "{\r\n  \"name\": \"app\",\r\n  \"engines\": {\r\n    \"npm\": \">=9\",\r\n    \"node\": \"20\"\r\n  }\r\n}\r\n"
+        );
+
+        let updated = set(&updated, "volta", "node", "20.12.2");
+        assert!(updated
+            .ends_with("  },\r\n  \"volta\": {\r\n    \"node\": \"20.12.2\"\r\n  }\r\n}\r\n"));
+        assert!(!updated.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        let code = |text: &str| {
+            set_nested_string(text, "engines", "node", "20")
+                .unwrap_err()
+                .code()
+        };
+
+        assert_eq!(code(r#"{"name": "#), "parse");
+        assert_eq!(code("[]"), "invalidInput");
+        assert_eq!(code(r#"{"engines": ">=18"}"#), "invalidInput");
+    }
+}
@@ -0,0 +1,369 @@
+use super::fnm_layout::FnmDir;
+use super::runner::run_blocking;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path};
+use tauri::command;
+
+/// sysconf 无法获取时钟频率时使用的默认值，Linux 上通常为 100Hz
+const DEFAULT_CLOCK_TICKS_PER_SECOND: u64 = 100;
+
+/// TCP 连接状态 LISTEN 在 /proc/net/tcp 中的编码
+const TCP_LISTEN: &str = "0A";
+
+/// 运行中的、来自 fnm 安装目录的 node 进程
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeProcess {
+    pub pid: u32,
+    /// 可执行文件所属的版本，如 v20.12.2
+    pub version: String,
+    pub exe: String,
+    /// 完整命令行
+    pub command: String,
+    /// 工作目录，无权读取时为 None
+    pub cwd: Option<String>,
+    /// 启动时间，Unix 毫秒时间戳
+    pub started_at: Option<u64>,
+    /// 正在监听的 TCP 端口
+    pub ports: Vec<u16>,
+}
+
+/// 列出使用 fnm 安装的 node 运行的进程
+#[command]
+pub async fn list_node_processes() -> Result<Vec<NodeProcess>, FnmGuiError> {
+    run_blocking(|| Ok(node_processes(&FnmDir::current()?))).await
+}
+
+/// 结束 node 进程，force 为 true 时强制结束
+/// 只允许结束 list_node_processes 中列出的进程
+#[command]
+pub async fn terminate_node_process(pid: u32, force: Option<bool>) -> Result<String, FnmGuiError> {
+    run_blocking(move || {
+        let dir = FnmDir::current()?;
+        if !node_processes(&dir)
+            .iter()
+            .any(|process| process.pid == pid)
+        {
+            return Err(FnmGuiError::ProcessNotFound(pid));
+        }
+        terminate(pid, force.unwrap_or(false))?;
+        Ok(format!("已结束进程 {}", pid))
+    })
+    .await
+}
+
+/// 查找使用 fnm 安装的 node 的进程，目前仅支持 Linux
+pub fn node_processes(dir: &FnmDir) -> Vec<NodeProcess> {
+    if cfg!(target_os = "linux") {
+        scan_proc(Path::new("/proc"), dir)
+    } else {
+        Vec::new()
+    }
+}
+
+/// 遍历 proc 文件系统中的进程，按 exe 链接判断是否属于 node-versions 下的某个版本
+/// 无权读取的进程会被跳过
+pub fn scan_proc(proc_root: &Path, dir: &FnmDir) -> Vec<NodeProcess> {
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+
+    let versions_dir = dir.node_versions_dir();
+    let versions_dir = fs::canonicalize(&versions_dir).unwrap_or(versions_dir);
+    let boot_time = read_boot_time(proc_root);
+    let listening = listening_sockets(proc_root);
+
+    let mut processes: Vec<NodeProcess> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let proc_dir = entry.path();
+            let exe = fs::read_link(proc_dir.join("exe")).ok()?;
+            // 可执行文件被删除后链接目标带有 " (deleted)" 后缀
+            let exe = exe.to_string_lossy();
+            let exe = exe.strip_suffix(" (deleted)").unwrap_or(&exe).to_string();
+
+            let version = match Path::new(&exe)
+                .strip_prefix(&versions_dir)
+                .ok()?
+                .components()
+                .next()?
+            {
+                Component::Normal(name) => name.to_str()?.to_string(),
+                _ => return None,
+            };
+
+            Some(NodeProcess {
+                pid,
+                version,
+                exe,
+                command: read_cmdline(&proc_dir),
+                cwd: fs::read_link(proc_dir.join("cwd"))
+                    .ok()
+                    .map(|cwd| cwd.to_string_lossy().to_string()),
+                started_at: boot_time.and_then(|boot| read_start_time(&proc_dir, boot)),
+                ports: listening_ports(&proc_dir, &listening),
+            })
+        })
+        .collect();
+
+    processes.sort_by_key(|process| process.pid);
+    processes
+}
+
+/// 以空格连接的命令行参数
+fn read_cmdline(proc_dir: &Path) -> String {
+    fs::read(proc_dir.join("cmdline"))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+/// 系统启动时间（Unix 秒），来自 /proc/stat 的 btime 行
+fn read_boot_time(proc_root: &Path) -> Option<u64> {
+    fs::read_to_string(proc_root.join("stat"))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// 进程启动时间：/proc/<pid>/stat 第 22 个字段为启动后经过的时钟周期数
+fn read_start_time(proc_dir: &Path, boot_time: u64) -> Option<u64> {
+    let stat = fs::read_to_string(proc_dir.join("stat")).ok()?;
+    // 第 2 个字段是括号中的进程名，可能包含空格，从最后一个 ')' 之后开始计数
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let ticks: u64 = rest.split_whitespace().nth(19)?.parse().ok()?;
+    Some(boot_time * 1000 + ticks * 1000 / clock_ticks_per_second())
+}
+
+/// /proc/<pid>/stat 中启动时间的单位：每秒的时钟滴答数（_SC_CLK_TCK）
+fn clock_ticks_per_second() -> u64 {
+    #[cfg(unix)]
+    {
+        // SAFETY: sysconf 只读取系统配置，没有前置条件
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            return ticks as u64;
+        }
+    }
+
+    DEFAULT_CLOCK_TICKS_PER_SECOND
+}
+
+/// 处于 LISTEN 状态的 socket inode 及其端口，来自 /proc/net/tcp 和 /proc/net/tcp6
+fn listening_sockets(proc_root: &Path) -> HashMap<u64, u16> {
+    let mut sockets = HashMap::new();
+
+    for table in ["tcp", "tcp6"] {
+        let Ok(content) = fs::read_to_string(proc_root.join("net").join(table)) else {
+            continue;
+        };
+
+        // 每行形如 "0: 00000000:1F90 00000000:0000 0A ... <uid> <timeout> <inode>"
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                continue;
+            }
+            let port = fields[1]
+                .rsplit(':')
+                .next()
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok());
+            if let (Some(port), Ok(inode)) = (port, fields[9].parse::<u64>()) {
+                sockets.insert(inode, port);
+            }
+        }
+    }
+
+    sockets
+}
+
+/// 根据 /proc/<pid>/fd 中的 socket:[inode] 链接找出进程监听的端口
+fn listening_ports(proc_dir: &Path, listening: &HashMap<u64, u16>) -> Vec<u16> {
+    let Ok(fds) = fs::read_dir(proc_dir.join("fd")) else {
+        return Vec::new();
+    };
+
+    let ports: BTreeSet<u16> = fds
+        .flatten()
+        .filter_map(|fd| {
+            let target = fs::read_link(fd.path()).ok()?;
+            let inode = target
+                .to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse::<u64>()
+                .ok()?;
+            listening.get(&inode).copied()
+        })
+        .collect();
+
+    ports.into_iter().collect()
+}
+
+/// 向进程发送 SIGTERM，force 为 true 时发送 SIGKILL
+#[cfg(unix)]
+fn terminate(pid: u32, force: bool) -> Result<(), FnmGuiError> {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    let target = libc::pid_t::try_from(pid).map_err(|_| FnmGuiError::ProcessNotFound(pid))?;
+
+    // SAFETY: kill 只向指定进程发送信号，没有内存安全方面的前置条件
+    if unsafe { libc::kill(target, signal) } != 0 {
+        let e = std::io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::ESRCH) {
+            return Err(FnmGuiError::ProcessNotFound(pid));
+        }
+        return Err(FnmGuiError::io(format!("结束进程 {} 失败", pid), e));
+    }
+
+    Ok(())
+}
+
+/// 通过 taskkill 结束进程，force 为 true 时添加 /F
+#[cfg(not(unix))]
+fn terminate(pid: u32, force: bool) -> Result<(), FnmGuiError> {
+    use super::runner::CommandOutput;
+
+    let mut args = vec!["/PID".to_string(), pid.to_string()];
+    if force {
+        args.push("/F".to_string());
+    }
+
+    let output = std::process::Command::new("taskkill")
+        .args(&args)
+        .output()
+        .map(CommandOutput::from)
+        .map_err(|e| FnmGuiError::spawn("taskkill", e))?;
+
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("taskkill", None, &output));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use std::path::PathBuf;
+
+    const BOOT_TIME: u64 = 1_700_000_000;
+
+    /// 进程名包含空格和 ')'，第 22 个字段（启动时间）为 ticks
+    fn stat_line(pid: u32, comm: &str, ticks: u64) -> String {
+        let mut fields: Vec<String> = (3..=52).map(|i| i.to_string()).collect();
+        fields[0] = "S".to_string();
+        fields[22 - 3] = ticks.to_string();
+        format!("{} ({}) {}\n", pid, comm, fields.join(" "))
+    }
+
+    /// 伪造的 /proc：stat、net/tcp、net/tcp6
+    fn fake_proc() -> tempfile::TempDir {
+        let proc_root = tempfile::tempdir().unwrap();
+        let root = proc_root.path();
+        fs::create_dir_all(root.join("net")).unwrap();
+        fs::write(
+            root.join("stat"),
+            format!("cpu  1 2 3\nbtime {}\n", BOOT_TIME),
+        )
+        .unwrap();
+
+        let header = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n";
+        fs::write(
+            root.join("net/tcp"),
+            format!(
+                "{header}   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 111 1 0000000000000000 100 0 0 10 0\n   \
+                 1: 0100007F:D431 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 222 1 0000000000000000 20 4 30 10 -1\n"
+            ),
+        )
+        .unwrap();
+        fs::write(
+            root.join("net/tcp6"),
+            format!(
+                "{header}   0: 00000000000000000000000000000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 333 1 0000000000000000 100 0 0 10 0\n"
+            ),
+        )
+        .unwrap();
+        proc_root
+    }
+
+    fn add_process(proc_root: &Path, pid: u32, exe: &Path, comm: &str) -> PathBuf {
+        let proc_dir = proc_root.join(pid.to_string());
+        fs::create_dir_all(proc_dir.join("fd")).unwrap();
+        symlink(exe, proc_dir.join("exe")).unwrap();
+        symlink("/srv/app", proc_dir.join("cwd")).unwrap();
+        fs::write(proc_dir.join("cmdline"), "node\0server.js\0--port\08080\0").unwrap();
+        fs::write(proc_dir.join("stat"), stat_line(pid, comm, 12_345)).unwrap();
+        proc_dir
+    }
+
+    #[test]
+    fn reads_start_time_after_the_last_paren() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("stat"), stat_line(42, "node ) (x) y", 500)).unwrap();
+
+        assert_eq!(
+            read_start_time(temp.path(), BOOT_TIME),
+            Some(BOOT_TIME * 1000 + 500 * 1000 / clock_ticks_per_second())
+        );
+    }
+
+    #[test]
+    fn reads_listening_sockets_from_tcp_and_tcp6() {
+        let proc_root = fake_proc();
+
+        let sockets = listening_sockets(proc_root.path());
+        assert_eq!(sockets, HashMap::from([(111, 8080), (333, 3000)]));
+    }
+
+    #[test]
+    fn scans_node_processes_from_a_fake_proc_tree() {
+        let fnm = tempfile::tempdir().unwrap();
+        let dir = FnmDir::new(fnm.path());
+        fs::create_dir_all(dir.installation_dir("v20.12.2")).unwrap();
+        let versions_dir = fs::canonicalize(dir.node_versions_dir()).unwrap();
+        let node = versions_dir.join("v20.12.2/installation/bin/node");
+
+        let proc_root = fake_proc();
+        let root = proc_root.path();
+        // 版本已被卸载，exe 链接带有 " (deleted)" 后缀
+        let deleted = PathBuf::from(format!("{} (deleted)", node.display()));
+        let proc_dir = add_process(root, 1234, &deleted, "node (main) )");
+        for (fd, target) in [
+            ("0", "/dev/null"),
+            ("3", "socket:[111]"),
+            ("4", "socket:[222]"),
+            ("5", "socket:[333]"),
+        ] {
+            symlink(target, proc_dir.join("fd").join(fd)).unwrap();
+        }
+        add_process(root, 99, Path::new("/usr/bin/node"), "node");
+        fs::create_dir_all(root.join("self")).unwrap();
+
+        let processes = scan_proc(root, &dir);
+        assert_eq!(
+            processes,
+            [NodeProcess {
+                pid: 1234,
+                version: "v20.12.2".to_string(),
+                exe: node.to_string_lossy().to_string(),
+                command: "node server.js --port 8080".to_string(),
+                cwd: Some("/srv/app".to_string()),
+                started_at: Some(BOOT_TIME * 1000 + 12_345 * 1000 / clock_ticks_per_second()),
+                ports: vec![3000, 8080],
+            }]
+        );
+    }
+}
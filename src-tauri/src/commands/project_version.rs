@@ -0,0 +1,634 @@
+use super::env::{read_fnm_env, FnmEnv};
+use super::fnm_layout::FnmDir;
+use super::package_json::set_nested_string;
+use super::runner::{run_blocking, ProcessRunner};
+use super::semver::{NodeVersion, VersionSpec};
+use super::settings::{save_version_sources, version_sources};
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// 项目中声明 Node 版本的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VersionSource {
+    /// .nvmrc 文件
+    Nvmrc,
+    /// .node-version 文件
+    NodeVersion,
+    /// package.json 的 engines.node，只在开启 resolve_engines 时读取
+    Engines,
+    /// asdf 的 .tool-versions 文件中的 nodejs 行
+    ToolVersions,
+    /// package.json 的 volta.node
+    Volta,
+    /// package.json 的 devEngines.runtime 中名为 node 的条目
+    DevEngines,
+}
+
+impl VersionSource {
+    /// 默认优先级：前三项与 fnm 一致（.nvmrc、.node-version、engines），fnm 不识别的来源排在后面
+    pub const DEFAULT_ORDER: [VersionSource; 6] = [
+        VersionSource::Nvmrc,
+        VersionSource::NodeVersion,
+        VersionSource::Engines,
+        VersionSource::ToolVersions,
+        VersionSource::Volta,
+        VersionSource::DevEngines,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            VersionSource::Nvmrc => ".nvmrc",
+            VersionSource::NodeVersion => ".node-version",
+            VersionSource::ToolVersions => ".tool-versions",
+            VersionSource::Engines | VersionSource::Volta | VersionSource::DevEngines => {
+                "package.json"
+            }
+        }
+    }
+
+    /// 读取项目目录中该来源声明的版本
+    pub fn read(self, project: &Path) -> Option<VersionSpecSource> {
+        let path = project.join(self.file_name());
+        let spec = match self {
+            VersionSource::Nvmrc | VersionSource::NodeVersion => read_version_file(&path),
+            VersionSource::ToolVersions => read_tool_versions(&path),
+            VersionSource::Engines => read_package_field(&path, "engines", "node"),
+            VersionSource::Volta => read_package_field(&path, "volta", "node"),
+            VersionSource::DevEngines => read_dev_engines(&path),
+        }?;
+
+        Some(VersionSpecSource {
+            file: path.to_string_lossy().to_string(),
+            spec,
+            source: self,
+        })
+    }
+}
+
+/// 项目中声明的 Node 版本及其来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionSpecSource {
+    /// 声明版本的文件路径
+    pub file: String,
+    /// 文件中的原始写法，如 "20"、"lts/iron"、">=18"
+    pub spec: String,
+    pub source: VersionSource,
+}
+
+/// 查找项目版本声明的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveOptions {
+    /// 是否逐级向上查找父目录（FNM_VERSION_FILE_STRATEGY=recursive）
+    pub recursive: bool,
+    /// 是否读取 package.json 的 engines.node（FNM_RESOLVE_ENGINES）
+    pub resolve_engines: bool,
+    /// 按优先级排列的来源，未列出的来源不读取
+    pub sources: Vec<VersionSource>,
+}
+
+impl ResolveOptions {
+    /// fnm 的配置加上应用中设置的来源优先级
+    pub fn from_env(env: &FnmEnv) -> Self {
+        ResolveOptions {
+            recursive: env.version_file_strategy == "recursive",
+            resolve_engines: env.resolve_engines,
+            sources: version_sources(),
+        }
+    }
+
+    /// 只查找项目目录本身，用于卸载影响分析和清理
+    pub fn local() -> Self {
+        ResolveOptions {
+            recursive: false,
+            resolve_engines: true,
+            sources: version_sources(),
+        }
+    }
+}
+
+/// 固定项目版本时写入的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PinFormat {
+    /// .node-version 文件
+    NodeVersion,
+    /// .nvmrc 文件
+    Nvmrc,
+    /// package.json 的 engines.node
+    Engines,
+    /// package.json 的 volta.node
+    Volta,
+}
+
+impl PinFormat {
+    fn source(self) -> VersionSource {
+        match self {
+            PinFormat::NodeVersion => VersionSource::NodeVersion,
+            PinFormat::Nvmrc => VersionSource::Nvmrc,
+            PinFormat::Engines => VersionSource::Engines,
+            PinFormat::Volta => VersionSource::Volta,
+        }
+    }
+
+    /// package.json 中的字段，如 ("engines", "node")
+    fn package_field(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            PinFormat::Engines => Some(("engines", "node")),
+            PinFormat::Volta => Some(("volta", "node")),
+            PinFormat::NodeVersion | PinFormat::Nvmrc => None,
+        }
+    }
+}
+
+/// 项目目录的版本解析结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectVersion {
+    pub path: String,
+    /// 使用的 FNM_VERSION_FILE_STRATEGY：local 或 recursive
+    pub strategy: String,
+    /// 生效的版本声明，没有找到时为 None
+    pub source: Option<VersionSpecSource>,
+    /// 本次查找使用的来源优先级
+    pub sources: Vec<VersionSource>,
+    /// 解析出的已安装版本
+    pub resolved: Option<String>,
+    /// 声明了版本但本地没有匹配的安装
+    pub install_required: bool,
+}
+
+/// 按 fnm 的配置（版本文件查找策略、是否读取 engines）和来源优先级解析项目目录使用的 Node 版本
+#[command]
+pub async fn resolve_project_version(path: String) -> Result<ProjectVersion, FnmGuiError> {
+    run_blocking(move || {
+        let env = read_fnm_env(&ProcessRunner)?;
+        let project = PathBuf::from(&path);
+        if !project.is_dir() {
+            return Err(FnmGuiError::InvalidInput(format!("{} 不是目录", path)));
+        }
+
+        Ok(resolve_project(
+            &project,
+            &FnmDir::current()?,
+            &ResolveOptions::from_env(&env),
+        ))
+    })
+    .await
+}
+
+/// 获取项目版本来源的优先级
+#[command]
+pub async fn get_version_sources() -> Result<Vec<VersionSource>, FnmGuiError> {
+    Ok(version_sources())
+}
+
+/// 保存项目版本来源的优先级，未列出的来源不再读取；为空时恢复默认顺序
+#[command]
+pub async fn set_version_sources(
+    sources: Option<Vec<VersionSource>>,
+) -> Result<Vec<VersionSource>, FnmGuiError> {
+    run_blocking(move || save_version_sources(sources.filter(|s| !s.is_empty()))).await
+}
+
+/// 将项目固定到指定的 Node 版本，写入版本文件或 package.json 中的字段
+/// package.json 只修改对应的值，保留原有格式和键的顺序
+/// 项目中已有不一致的版本声明时需要 force 为 true 才会写入
+#[command]
+pub async fn pin_project_version(
+    path: String,
+    version: String,
+    format: PinFormat,
+    force: Option<bool>,
+) -> Result<VersionSpecSource, FnmGuiError> {
+    run_blocking(move || {
+        let project = PathBuf::from(&path);
+        if !project.is_dir() {
+            return Err(FnmGuiError::InvalidInput(format!("{} 不是目录", path)));
+        }
+        pin(&project, &version, format, force.unwrap_or(false))
+    })
+    .await
+}
+
+pub fn pin(
+    project: &Path,
+    version: &str,
+    format: PinFormat,
+    force: bool,
+) -> Result<VersionSpecSource, FnmGuiError> {
+    let version = version.trim();
+    if version.is_empty() || version.contains(['\n', '\r', '#']) {
+        return Err(FnmGuiError::InvalidInput(format!(
+            "无效的版本 \"{}\"",
+            version
+        )));
+    }
+    if format.package_field().is_some()
+        && !matches!(VersionSpec::parse(version), VersionSpec::Range(_))
+    {
+        return Err(FnmGuiError::InvalidInput(format!(
+            "package.json 中只能写入版本号或范围，不支持 \"{}\"",
+            version
+        )));
+    }
+
+    if !force {
+        let reasons: Vec<String> = VersionSource::DEFAULT_ORDER
+            .iter()
+            .filter_map(|source| source.read(project))
+            .filter(|existing| {
+                !pins_agree(existing.source == format.source(), &existing.spec, version)
+            })
+            .map(|existing| format!("{} 中已声明 {}", existing.file, existing.spec))
+            .collect();
+        if !reasons.is_empty() {
+            return Err(FnmGuiError::ConfirmationRequired {
+                operation: "固定项目版本".to_string(),
+                reasons,
+            });
+        }
+    }
+
+    let path = project.join(format.source().file_name());
+    match format.package_field() {
+        Some((outer, inner)) => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| FnmGuiError::io("读取 package.json 失败", e))?;
+            let updated = set_nested_string(&content, outer, inner, version)?;
+            fs::write(&path, updated).map_err(|e| FnmGuiError::io("写入 package.json 失败", e))?;
+        }
+        None => {
+            fs::write(&path, format!("{}\n", version))
+                .map_err(|e| FnmGuiError::io("写入版本文件失败", e))?;
+        }
+    }
+
+    Ok(VersionSpecSource {
+        file: path.to_string_lossy().to_string(),
+        spec: version.to_string(),
+        source: format.source(),
+    })
+}
+
+/// 已有的版本声明与新版本是否一致：写法相同（忽略 v 前缀），
+/// 或者已有的是其他位置的范围且新版本是满足该范围的完整版本号
+fn pins_agree(same_target: bool, existing: &str, version: &str) -> bool {
+    let strip = |v: &str| v.trim().trim_start_matches(['v', 'V']).to_string();
+    if strip(existing) == strip(version) {
+        return true;
+    }
+    if same_target {
+        return false;
+    }
+    match (VersionSpec::parse(existing), NodeVersion::parse(version)) {
+        (VersionSpec::Range(req), Some(version)) => req.matches(&version),
+        _ => false,
+    }
+}
+
+pub fn resolve_project(project: &Path, dir: &FnmDir, options: &ResolveOptions) -> ProjectVersion {
+    let source = find_version_spec(project, options);
+    let resolved = source
+        .as_ref()
+        .and_then(|source| resolve_installed(&source.spec, dir));
+
+    ProjectVersion {
+        path: project.to_string_lossy().to_string(),
+        strategy: if options.recursive {
+            "recursive"
+        } else {
+            "local"
+        }
+        .to_string(),
+        sources: options.sources.clone(),
+        install_required: source.is_some() && resolved.is_none(),
+        source,
+        resolved,
+    }
+}
+
+/// 查找生效的版本声明：local 策略只看项目目录，recursive 策略逐级向上查找父目录
+/// 同一目录中按来源优先级取第一个声明了版本的来源
+pub fn find_version_spec(project: &Path, options: &ResolveOptions) -> Option<VersionSpecSource> {
+    let mut current = Some(project);
+    while let Some(dir) = current {
+        let found = options
+            .sources
+            .iter()
+            .filter(|source| options.resolve_engines || **source != VersionSource::Engines)
+            .find_map(|source| source.read(dir));
+        if found.is_some() || !options.recursive {
+            return found;
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+/// 读取项目目录中声明的 Node 版本，按设置的来源优先级查找，不查找父目录
+pub fn read_version_spec(project: &Path) -> Option<VersionSpecSource> {
+    find_version_spec(project, &ResolveOptions::local())
+}
+
+/// 读取 .nvmrc/.node-version 中第一行有效内容（忽略空行和 # 注释）
+fn read_version_file(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+}
+
+/// 读取 .tool-versions 中 nodejs 行的第一个版本（后面的是备选版本）
+/// asdf 的 lts、lts-<codename> 写法转换为 fnm 的 lts/*、lts/<codename>
+fn read_tool_versions(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let version = content.lines().find_map(|line| {
+        let mut fields = line.split('#').next().unwrap_or("").split_whitespace();
+        matches!(fields.next(), Some("nodejs" | "node")).then(|| fields.next())?
+    })?;
+
+    Some(match version {
+        "lts" => "lts/*".to_string(),
+        _ => match version.strip_prefix("lts-") {
+            Some(codename) => format!("lts/{}", codename),
+            None => version.to_string(),
+        },
+    })
+}
+
+fn read_package_json(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 读取 package.json 中 outer.inner 的字符串值
+fn read_package_field(path: &Path, outer: &str, inner: &str) -> Option<String> {
+    let package = read_package_json(path)?;
+    let spec = package.get(outer)?.get(inner)?.as_str()?.trim();
+    (!spec.is_empty()).then(|| spec.to_string())
+}
+
+/// 读取 devEngines.runtime 中 name 为 node 的 version，runtime 可以是对象或对象数组
+fn read_dev_engines(path: &Path) -> Option<String> {
+    let package = read_package_json(path)?;
+    let runtime = package.get("devEngines")?.get("runtime")?;
+    let entries = match runtime {
+        serde_json::Value::Array(entries) => entries.iter().collect(),
+        entry => vec![entry],
+    };
+
+    entries.into_iter().find_map(|entry| {
+        if entry.get("name")?.as_str()? != "node" {
+            return None;
+        }
+        let spec = entry.get("version")?.as_str()?.trim();
+        (!spec.is_empty()).then(|| spec.to_string())
+    })
+}
+
+/// 将版本声明解析为已安装的版本，与 fnm use 的规则一致：
+/// 版本号、部分版本号和范围取匹配的最高版本，lts/<codename> 和其他名称按别名查找
+pub fn resolve_installed(spec: &str, dir: &FnmDir) -> Option<String> {
+    match VersionSpec::parse(spec) {
+        VersionSpec::Lts(codename) => {
+            let alias = match codename {
+                Some(codename) => format!("lts-{}", codename),
+                None => "lts-latest".to_string(),
+            };
+            alias_version(dir, &alias)
+        }
+        VersionSpec::Alias(name) => alias_version(dir, &name),
+        parsed => {
+            let installed = dir.installed_versions();
+            parsed
+                .max_installed(installed.iter().map(String::as_str))
+                .map(str::to_string)
+        }
+    }
+}
+
+fn alias_version(dir: &FnmDir, name: &str) -> Option<String> {
+    // 别名是 aliases 目录下的文件名，不能包含路径分隔符
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+    dir.alias(name)
+        .filter(|alias| !alias.dangling)
+        .and_then(|alias| alias.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(sources: &[VersionSource]) -> ResolveOptions {
+        ResolveOptions {
+            recursive: false,
+            resolve_engines: true,
+            sources: sources.to_vec(),
+        }
+    }
+
+    #[test]
+    fn nvmrc_wins_over_node_version_by_default() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".node-version"), "20.12.2\n").unwrap();
+        fs::write(
+            project.path().join(".nvmrc"),
+            "# 与 fnm 相同，优先读取\nlts/iron\n",
+        )
+        .unwrap();
+        fs::write(
+            project.path().join("package.json"),
+            r#"{"engines":{"node":">=18"}}"#,
+        )
+        .unwrap();
+
+        let found = find_version_spec(project.path(), &options(&VersionSource::DEFAULT_ORDER));
+        let found = found.unwrap();
+        assert_eq!(found.source, VersionSource::Nvmrc);
+        assert_eq!(found.spec, "lts/iron");
+
+        fs::remove_file(project.path().join(".nvmrc")).unwrap();
+        let found = find_version_spec(project.path(), &options(&VersionSource::DEFAULT_ORDER));
+        assert_eq!(found.unwrap().source, VersionSource::NodeVersion);
+
+        fs::remove_file(project.path().join(".node-version")).unwrap();
+        let found = find_version_spec(project.path(), &options(&VersionSource::DEFAULT_ORDER));
+        assert_eq!(found.unwrap().source, VersionSource::Engines);
+    }
+
+    #[test]
+    fn custom_order_overrides_default() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".node-version"), "20.12.2").unwrap();
+        fs::write(project.path().join(".nvmrc"), "18").unwrap();
+
+        let found = find_version_spec(
+            project.path(),
+            &options(&[VersionSource::NodeVersion, VersionSource::Nvmrc]),
+        );
+        assert_eq!(found.unwrap().spec, "20.12.2");
+    }
+
+    #[test]
+    fn reads_node_from_tool_versions() {
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join(".tool-versions");
+        let read_spec = |content: &str| {
+            fs::write(&path, content).unwrap();
+            read_tool_versions(&path)
+        };
+
+        let content = "# asdf\n\npython 3.12.1\nnodejs 20.12.2 18.20.8 system # 备选版本\n";
+        assert_eq!(read_spec(content).as_deref(), Some("20.12.2"));
+        assert_eq!(read_spec("nodejs lts\n").as_deref(), Some("lts/*"));
+        assert_eq!(read_spec("nodejs lts-iron\n").as_deref(), Some("lts/iron"));
+        assert_eq!(read_spec("  node   22  \n").as_deref(), Some("22"));
+        assert_eq!(read_spec("# nodejs 20\nruby 3.3.0\n"), None);
+        assert_eq!(read_spec("nodejs\nnodejs 18\n").as_deref(), Some("18"));
+        assert_eq!(read_spec(""), None);
+    }
+
+    #[test]
+    fn reads_node_from_dev_engines_runtime() {
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("package.json");
+        let read_spec = |runtime: &str| {
+            fs::write(
+                &path,
+                format!(r#"{{"devEngines":{{"runtime":{}}}}}"#, runtime),
+            )
+            .unwrap();
+            read_dev_engines(&path)
+        };
+
+        let object = r#"{"name":"node","version":">=20","onFail":"error"}"#;
+        assert_eq!(read_spec(object).as_deref(), Some(">=20"));
+        let array = r#"[{"name":"bun","version":"1.1"},{"name":"node","version":" ^22 "}]"#;
+        assert_eq!(read_spec(array).as_deref(), Some("^22"));
+
+        assert_eq!(read_spec(r#"{"name":"deno","version":"2"}"#), None);
+        assert_eq!(read_spec(r#"[{"name":"bun","version":"1.1"}]"#), None);
+        assert_eq!(read_spec(r#"{"name":"node"}"#), None);
+        assert_eq!(read_spec(r#"{"name":"node","version":""}"#), None);
+        assert_eq!(read_spec("[]"), None);
+    }
+
+    fn read(path: impl AsRef<Path>) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn pins_agree_with_equal_specs_and_satisfied_ranges() {
+        let cases = [
+            (false, "v20.12.2", "20.12.2", true),
+            (true, " 20 ", "V20", true),
+            (false, ">=18", "20.12.2", true),
+            (false, "^20.10.0", "20.12.2", true),
+            (true, ">=18", "20.12.2", false),
+            (false, "^18", "20.12.2", false),
+            (false, ">=18", ">=20", false),
+            (false, "lts/iron", "20.12.2", false),
+        ];
+
+        for (same_target, existing, version, agree) in cases {
+            assert_eq!(
+                pins_agree(same_target, existing, version),
+                agree,
+                "{existing:?} {version:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn refuses_a_conflicting_pin_without_force() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".nvmrc"), "18\n").unwrap();
+
+        let e = pin(project.path(), "20.12.2", PinFormat::NodeVersion, false).unwrap_err();
+        assert_eq!(e.code(), "confirmationRequired");
+        match e {
+            FnmGuiError::ConfirmationRequired { reasons, .. } => {
+                assert_eq!(reasons.len(), 1);
+                assert!(reasons[0].ends_with(".nvmrc 中已声明 18"), "{reasons:?}");
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(!project.path().join(".node-version").exists());
+
+        let pinned = pin(project.path(), "20.12.2", PinFormat::NodeVersion, true).unwrap();
+        assert_eq!(pinned.source, VersionSource::NodeVersion);
+        assert_eq!(read(project.path().join(".node-version")), "20.12.2\n");
+        assert_eq!(read(project.path().join(".nvmrc")), "18\n");
+    }
+
+    #[test]
+    fn pins_without_force_when_declarations_agree() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".nvmrc"), "v20.12.2\n").unwrap();
+        fs::write(
+            project.path().join("package.json"),
+            "{\n  \"name\": \"app\",\n  \"engines\": {\n    \"node\": \">=18\"\n  }\n}\n",
+        )
+        .unwrap();
+
+        pin(project.path(), "20.12.2", PinFormat::NodeVersion, false).unwrap();
+        assert_eq!(read(project.path().join(".node-version")), "20.12.2\n");
+
+        pin(project.path(), "20.12.2", PinFormat::Volta, false).unwrap();
+        assert_eq!(
+            read(project.path().join("package.json")),
+            "{\n  \"name\": \"app\",\n  \"engines\": {\n    \"node\": \">=18\"\n  },\n  \"volta\": {\n    \"node\": \"20.12.2\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn replaces_a_package_json_pin_only_with_force() {
+        let project = tempfile::tempdir().unwrap();
+        let package_json = project.path().join("package.json");
+        fs::write(
+            &package_json,
+            r#"{"engines":{"node":">=18"},"private":true}"#,
+        )
+        .unwrap();
+
+        let e = pin(project.path(), ">=20", PinFormat::Engines, false).unwrap_err();
+        assert_eq!(e.code(), "confirmationRequired");
+        assert_eq!(
+            read(&package_json),
+            r#"{"engines":{"node":">=18"},"private":true}"#
+        );
+
+        pin(project.path(), ">=20", PinFormat::Engines, true).unwrap();
+        assert_eq!(
+            read(&package_json),
+            r#"{"engines":{"node":">=20"},"private":true}"#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_pins() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join("package.json"), "{}").unwrap();
+
+        for (version, format) in [
+            ("", PinFormat::Nvmrc),
+            ("20\n18", PinFormat::Nvmrc),
+            ("20 # comment", PinFormat::NodeVersion),
+            ("lts/iron", PinFormat::Engines),
+            ("system", PinFormat::Volta),
+        ] {
+            let e = pin(project.path(), version, format, true).unwrap_err();
+            assert_eq!(e.code(), "invalidInput", "{version:?}");
+        }
+        assert_eq!(read(project.path().join("package.json")), "{}");
+    }
+}
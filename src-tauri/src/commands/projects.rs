@@ -0,0 +1,277 @@
+use super::env::read_fnm_env;
+use super::fnm_layout::FnmDir;
+use super::project_version::{resolve_installed, resolve_project, ResolveOptions, VersionSource};
+use super::runner::{run_blocking, ProcessRunner};
+use super::semver::{NodeVersion, VersionSpec};
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{command, State};
+
+/// 项目列表文件名，位于应用数据目录下
+const PROJECTS_FILE: &str = "projects.json";
+
+/// 递归查找项目时默认的最大目录深度
+const DEFAULT_DISCOVERY_DEPTH: u32 = 4;
+
+/// 递归查找项目时跳过的目录
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", "dist"];
+
+/// 记录在应用中的项目目录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEntry {
+    pub path: String,
+    /// 加入时间，Unix 毫秒时间戳
+    pub added_at: u64,
+}
+
+/// 项目对 Node 版本的要求及本地的满足情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStatus {
+    pub path: String,
+    pub added_at: u64,
+    /// 项目目录是否仍然存在
+    pub exists: bool,
+    /// 声明版本的文件，如 .nvmrc
+    pub file: Option<String>,
+    /// 生效的版本来源
+    pub source: Option<VersionSource>,
+    /// 声明的版本，如 "20"、">=18 <21"
+    pub spec: Option<String>,
+    /// 匹配的已安装版本
+    pub resolved: Option<String>,
+    /// 声明了版本且本地已安装匹配的版本
+    pub installed: bool,
+    /// 当前 default 别名指向的版本
+    pub default_version: Option<String>,
+    /// default 是否满足声明的版本，没有声明版本或没有 default 时为 None
+    pub default_satisfies: Option<bool>,
+}
+
+/// 应用记住的项目目录，保存在应用数据目录下，用于检查项目对 Node 版本的依赖
+#[derive(Default)]
+pub struct ProjectRegistry {
+    projects: Mutex<Vec<ProjectEntry>>,
+    /// 项目列表文件，为 None 时只保存在内存中
+    file: Option<PathBuf>,
+}
+
+impl ProjectRegistry {
+    /// 读取项目列表，文件不存在或无法解析时为空
+    pub fn load(data_dir: PathBuf) -> Self {
+        let file = data_dir.join(PROJECTS_FILE);
+        let projects = fs::read_to_string(&file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        ProjectRegistry {
+            projects: Mutex::new(projects),
+            file: Some(file),
+        }
+    }
+
+    /// 全部项目，按加入顺序排列
+    pub fn list(&self) -> Vec<ProjectEntry> {
+        self.projects.lock().unwrap().clone()
+    }
+
+    /// 加入项目，已经存在的项目保持原样，返回新加入的项目
+    pub fn add(&self, paths: &[String]) -> Result<Vec<ProjectEntry>, FnmGuiError> {
+        let added_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut added = Vec::new();
+        self.update(|projects| {
+            for path in paths {
+                if projects.iter().any(|project| &project.path == path) {
+                    continue;
+                }
+                let entry = ProjectEntry {
+                    path: path.clone(),
+                    added_at,
+                };
+                projects.push(entry.clone());
+                added.push(entry);
+            }
+        })?;
+        Ok(added)
+    }
+
+    /// 移除项目，返回是否存在
+    pub fn remove(&self, path: &str) -> Result<bool, FnmGuiError> {
+        let mut removed = false;
+        self.update(|projects| {
+            let before = projects.len();
+            projects.retain(|project| project.path != path);
+            removed = projects.len() != before;
+        })?;
+        Ok(removed)
+    }
+
+    /// 修改项目列表并写入文件，写入失败时内存中的列表保持不变
+    fn update(&self, change: impl FnOnce(&mut Vec<ProjectEntry>)) -> Result<(), FnmGuiError> {
+        let mut state = self.projects.lock().unwrap();
+
+        let mut projects = state.clone();
+        change(&mut projects);
+        if projects == *state {
+            return Ok(());
+        }
+
+        if let Some(ref file) = self.file {
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| FnmGuiError::io("创建应用数据目录失败", e))?;
+            }
+            let content = serde_json::to_string_pretty(&projects)
+                .map_err(|e| FnmGuiError::parse("项目列表", e))?;
+            fs::write(file, content).map_err(|e| FnmGuiError::io("保存项目列表失败", e))?;
+        }
+
+        *state = projects;
+        Ok(())
+    }
+}
+
+/// 加入项目目录，recursive 为 true 时加入目录下所有声明了 Node 版本或包含 package.json 的项目
+/// 返回新加入的项目
+#[command]
+pub async fn add_project(
+    projects: State<'_, ProjectRegistry>,
+    path: String,
+    recursive: Option<bool>,
+    max_depth: Option<u32>,
+) -> Result<Vec<ProjectEntry>, FnmGuiError> {
+    let paths = run_blocking(move || {
+        let root = fs::canonicalize(&path).map_err(|e| FnmGuiError::io("读取项目目录失败", e))?;
+        if !root.is_dir() {
+            return Err(FnmGuiError::InvalidInput(format!("{} 不是目录", path)));
+        }
+
+        Ok(if recursive.unwrap_or(false) {
+            discover_projects(&root, max_depth.unwrap_or(DEFAULT_DISCOVERY_DEPTH))
+        } else {
+            vec![root]
+        })
+    })
+    .await?;
+
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    projects.add(&paths)
+}
+
+/// 移除项目目录，只修改项目列表，不会删除任何文件
+#[command]
+pub async fn remove_project(
+    projects: State<'_, ProjectRegistry>,
+    path: String,
+) -> Result<String, FnmGuiError> {
+    if !projects.remove(&path)? {
+        return Err(FnmGuiError::InvalidInput(format!(
+            "项目 {} 不在列表中",
+            path
+        )));
+    }
+    Ok(format!("已移除项目 {}", path))
+}
+
+/// 检查所有项目声明的 Node 版本、是否已安装以及 default 是否满足
+#[command]
+pub async fn scan_projects(
+    projects: State<'_, ProjectRegistry>,
+) -> Result<Vec<ProjectStatus>, FnmGuiError> {
+    let projects = projects.list();
+    run_blocking(move || {
+        let env = read_fnm_env(&ProcessRunner)?;
+        let options = ResolveOptions::from_env(&env);
+        let dir = FnmDir::current()?;
+        Ok(projects
+            .iter()
+            .map(|project| project_status(project, &dir, &options))
+            .collect())
+    })
+    .await
+}
+
+pub fn project_status(
+    project: &ProjectEntry,
+    dir: &FnmDir,
+    options: &ResolveOptions,
+) -> ProjectStatus {
+    let path = Path::new(&project.path);
+    let exists = path.is_dir();
+    let resolution = exists.then(|| resolve_project(path, dir, options));
+    let source = resolution.as_ref().and_then(|r| r.source.clone());
+    let resolved = resolution.and_then(|r| r.resolved);
+    let default_version = dir.default_version();
+
+    ProjectStatus {
+        path: project.path.clone(),
+        added_at: project.added_at,
+        exists,
+        installed: resolved.is_some(),
+        default_satisfies: match (&source, &default_version) {
+            (Some(source), Some(default)) => Some(satisfies(&source.spec, default, dir)),
+            _ => None,
+        },
+        file: source.as_ref().map(|s| s.file.clone()),
+        source: source.as_ref().map(|s| s.source),
+        spec: source.map(|s| s.spec),
+        resolved,
+        default_version,
+    }
+}
+
+/// 版本是否满足版本声明：范围直接比较，lts、latest 和别名比较解析出的版本
+fn satisfies(spec: &str, version: &str, dir: &FnmDir) -> bool {
+    match VersionSpec::parse(spec) {
+        VersionSpec::Range(req) => NodeVersion::parse(version).is_some_and(|v| req.matches(&v)),
+        _ => resolve_installed(spec, dir).as_deref() == Some(version),
+    }
+}
+
+/// 在目录下查找项目：包含版本文件或 package.json 的目录
+/// 找到项目后不再进入其子目录，跳过隐藏目录和 node_modules 等构建目录
+pub fn discover_projects(root: &Path, max_depth: u32) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        let is_project = VersionSource::DEFAULT_ORDER
+            .iter()
+            .any(|source| dir.join(source.file_name()).is_file());
+        if is_project {
+            found.push(dir);
+            continue;
+        }
+        if depth >= max_depth {
+            continue;
+        }
+
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // 不跟随符号链接，避免循环
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+
+    found.sort();
+    found
+}
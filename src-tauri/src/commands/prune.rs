@@ -0,0 +1,494 @@
+use super::disk_usage::dir_size;
+use super::fnm_layout::{compare_versions, release_line, FnmDir};
+use super::jobs::JobManager;
+use super::processes::{node_processes, NodeProcess};
+use super::project_version::{read_version_spec, resolve_installed};
+use super::projects::{ProjectEntry, ProjectRegistry};
+use super::runner::run_blocking;
+use super::settings::{prune_policy, save_prune_policy, PrunePolicy};
+use super::version::uninstall_version;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{command, State};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// 清理计划中的一个版本及其原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneEntry {
+    pub version: String,
+    pub reasons: Vec<String>,
+    /// 最近一次使用时间，Unix 毫秒时间戳
+    pub last_used_at: Option<u64>,
+    pub bytes: u64,
+}
+
+/// 按清理策略计算出的结果，不会修改任何文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunePlan {
+    pub policy: PrunePolicy,
+    /// 将被卸载的版本
+    pub remove: Vec<PruneEntry>,
+    /// 保留的版本
+    pub keep: Vec<PruneEntry>,
+    /// 卸载后可释放的空间（字节）
+    pub reclaimable_bytes: u64,
+}
+
+/// 单个版本卸载失败的原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneFailure {
+    pub version: String,
+    pub error: String,
+}
+
+/// 执行清理的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneResult {
+    pub removed: Vec<String>,
+    pub failed: Vec<PruneFailure>,
+}
+
+/// 获取保存的清理策略
+#[command]
+pub async fn get_prune_policy() -> Result<PrunePolicy, FnmGuiError> {
+    Ok(prune_policy())
+}
+
+/// 保存清理策略
+#[command]
+pub async fn set_prune_policy(policy: PrunePolicy) -> Result<PrunePolicy, FnmGuiError> {
+    run_blocking(move || save_prune_policy(policy)).await
+}
+
+/// 预览清理结果，policy 为空时使用保存的策略
+#[command]
+pub async fn preview_prune(
+    projects: State<'_, ProjectRegistry>,
+    policy: Option<PrunePolicy>,
+) -> Result<PrunePlan, FnmGuiError> {
+    let projects = projects.list();
+    let policy = policy.unwrap_or_else(prune_policy);
+    run_blocking(move || {
+        let dir = FnmDir::current()?;
+        let processes = node_processes(&dir);
+        Ok(plan(
+            &dir,
+            &policy,
+            &projects,
+            &processes,
+            SystemTime::now(),
+        ))
+    })
+    .await
+}
+
+/// 逐个卸载用户在预览中确认的版本，单个版本失败不影响其他版本
+/// 清理计划已经列出了原因，卸载时不再逐个确认；预览之后不再满足清理条件的版本
+/// （如开始运行或被新项目引用）会被拒绝
+#[command]
+pub async fn apply_prune(
+    jobs: State<'_, JobManager>,
+    projects: State<'_, ProjectRegistry>,
+    versions: Vec<String>,
+    policy: Option<PrunePolicy>,
+) -> Result<PruneResult, FnmGuiError> {
+    let plan = preview_prune(projects.clone(), policy).await?;
+    let (confirmed, mut result) = confirm_candidates(&plan, versions);
+
+    for version in confirmed {
+        match uninstall_version(jobs.clone(), projects.clone(), version.clone(), Some(true)).await {
+            Ok(_) => result.removed.push(version),
+            Err(e) => result.failed.push(PruneFailure {
+                version,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(result)
+}
+
+/// 将预览时确认的版本与最新的清理计划比对，返回仍可清理的版本，
+/// 其余版本记为失败
+fn confirm_candidates(plan: &PrunePlan, versions: Vec<String>) -> (Vec<String>, PruneResult) {
+    let mut result = PruneResult {
+        removed: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut confirmed: Vec<String> = Vec::new();
+
+    for version in versions {
+        if confirmed.contains(&version) {
+            continue;
+        }
+        if plan.remove.iter().any(|entry| entry.version == version) {
+            confirmed.push(version);
+            continue;
+        }
+
+        let error = match plan.keep.iter().find(|entry| entry.version == version) {
+            Some(entry) => format!("已不满足清理条件：{}", entry.reasons.join("；")),
+            None => "版本未安装".to_string(),
+        };
+        result.failed.push(PruneFailure { version, error });
+    }
+
+    (confirmed, result)
+}
+
+/// 计算清理计划
+/// 正在运行的版本始终保留；keep_referenced 开启时保留被别名或项目引用的版本
+pub fn plan(
+    dir: &FnmDir,
+    policy: &PrunePolicy,
+    projects: &[ProjectEntry],
+    processes: &[NodeProcess],
+    now: SystemTime,
+) -> PrunePlan {
+    let versions = dir.installed_versions();
+    let references = references(dir, projects);
+    let outdated = outdated_patches(&versions, policy.keep_latest_per_major);
+
+    let mut plan = PrunePlan {
+        policy: policy.clone(),
+        remove: Vec::new(),
+        keep: Vec::new(),
+        reclaimable_bytes: 0,
+    };
+
+    for version in versions {
+        let last_used = last_used(dir, &version);
+        let mut keep_reasons = Vec::new();
+        let mut remove_reasons = Vec::new();
+
+        if processes.iter().any(|process| process.version == version) {
+            keep_reasons.push("有 node 进程正在运行".to_string());
+        }
+        if policy.keep_referenced {
+            if let Some(names) = references.get(&version) {
+                keep_reasons.push(format!("被 {} 引用", names.join("、")));
+            }
+        }
+
+        if let Some(newer) = outdated.get(&version) {
+            remove_reasons.push(format!("同一主版本已有更新的 {}", newer.join("、")));
+        }
+        if let Some(days) = policy.remove_unused_after_days {
+            let threshold = Duration::from_secs(u64::from(days) * SECONDS_PER_DAY);
+            // 无法确定使用时间时不按此规则清理
+            let unused = last_used
+                .and_then(|time| now.duration_since(time).ok())
+                .is_some_and(|elapsed| elapsed > threshold);
+            if unused {
+                remove_reasons.push(format!("超过 {} 天未使用", days));
+            }
+        }
+
+        let bytes = dir_size(&dir.node_versions_dir().join(&version));
+        let removable = keep_reasons.is_empty() && !remove_reasons.is_empty();
+        let entry = PruneEntry {
+            reasons: if removable {
+                remove_reasons
+            } else if keep_reasons.is_empty() {
+                vec!["不满足任何清理规则".to_string()]
+            } else {
+                keep_reasons
+            },
+            last_used_at: last_used.map(to_millis),
+            version,
+            bytes,
+        };
+
+        if removable {
+            plan.reclaimable_bytes += entry.bytes;
+            plan.remove.push(entry);
+        } else {
+            plan.keep.push(entry);
+        }
+    }
+
+    plan
+}
+
+/// 被别名和已记录项目引用的版本，值为引用者的描述
+fn references(dir: &FnmDir, projects: &[ProjectEntry]) -> HashMap<String, Vec<String>> {
+    let mut references: HashMap<String, Vec<String>> = HashMap::new();
+
+    for alias in dir.aliases().into_iter().filter(|alias| !alias.dangling) {
+        if let Some(version) = alias.version {
+            references
+                .entry(version)
+                .or_default()
+                .push(format!("别名 {}", alias.name));
+        }
+    }
+
+    for project in projects {
+        let resolved = read_version_spec(Path::new(&project.path))
+            .and_then(|source| resolve_installed(&source.spec, dir));
+        if let Some(version) = resolved {
+            references
+                .entry(version)
+                .or_default()
+                .push(format!("项目 {}", project.path));
+        }
+    }
+
+    references
+}
+
+/// 每个主版本中超出最新 keep 个之外的版本，值为该主版本中保留的较新版本
+fn outdated_patches(versions: &[String], keep: Option<u32>) -> HashMap<String, Vec<String>> {
+    let Some(keep) = keep else {
+        return HashMap::new();
+    };
+    let keep = keep.max(1) as usize;
+
+    let mut by_major: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for version in versions {
+        by_major
+            .entry(release_line(version))
+            .or_default()
+            .push(version);
+    }
+
+    let mut outdated = HashMap::new();
+    for mut line in by_major.into_values() {
+        line.sort_by(|a, b| compare_versions(b, a));
+        let newest: Vec<String> = line.iter().take(keep).map(|v| v.to_string()).collect();
+        for version in line.into_iter().skip(keep) {
+            outdated.insert(version.clone(), newest.clone());
+        }
+    }
+    outdated
+}
+
+/// 最近一次使用时间：指向该版本的 shell 会话链接中最晚的创建时间
+/// 不使用 node 可执行文件的访问时间：relatime、noatime 挂载和备份、杀毒软件的扫描都会让它失真。
+/// 会话链接随 fnm env 创建、fnm use 替换，重启后会被清空，找不到时返回 None，不按未使用天数清理
+fn last_used(dir: &FnmDir, version: &str) -> Option<SystemTime> {
+    dir.multishells()
+        .into_iter()
+        .filter(|session| session.version.as_deref() == Some(version))
+        .filter_map(|session| {
+            fs::symlink_metadata(&session.path)
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .max()
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    /// 临时 fnm 目录，已安装 installed 中的版本
+    fn fixture(installed: &[&str]) -> (TempDir, FnmDir) {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = FnmDir::new(temp.path().join("fnm"))
+            .with_multishell_root(temp.path().join("fnm_multishells"));
+        for version in installed {
+            fs::create_dir_all(dir.installation_dir(version)).unwrap();
+        }
+        fs::create_dir_all(dir.aliases_dir()).unwrap();
+        fs::create_dir_all(dir.multishell_root().unwrap()).unwrap();
+        (temp, dir)
+    }
+
+    #[cfg(unix)]
+    fn link(target: &Path, link: &Path) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    #[cfg(windows)]
+    fn link(target: &Path, link: &Path) {
+        std::os::windows::fs::symlink_dir(target, link).unwrap();
+    }
+
+    fn policy(keep_latest: Option<u32>, unused_days: Option<u32>, referenced: bool) -> PrunePolicy {
+        PrunePolicy {
+            keep_latest_per_major: keep_latest,
+            remove_unused_after_days: unused_days,
+            keep_referenced: referenced,
+        }
+    }
+
+    fn process(version: &str) -> NodeProcess {
+        NodeProcess {
+            pid: 1234,
+            version: version.to_string(),
+            exe: String::new(),
+            command: "node server.js".to_string(),
+            cwd: None,
+            started_at: None,
+            ports: Vec::new(),
+        }
+    }
+
+    fn versions(entries: &[PruneEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.version.as_str()).collect()
+    }
+
+    fn reasons<'a>(plan: &'a PrunePlan, version: &str) -> &'a [String] {
+        plan.remove
+            .iter()
+            .chain(&plan.keep)
+            .find(|entry| entry.version == version)
+            .map(|entry| entry.reasons.as_slice())
+            .unwrap()
+    }
+
+    const INSTALLED: [&str; 5] = ["v18.19.0", "v18.20.8", "v20.11.0", "v20.12.2", "v22.1.0"];
+
+    #[test]
+    fn keeps_the_latest_patches_per_major() {
+        let (_temp, dir) = fixture(&INSTALLED);
+
+        let pruned = plan(
+            &dir,
+            &policy(Some(1), None, false),
+            &[],
+            &[],
+            SystemTime::now(),
+        );
+        assert_eq!(versions(&pruned.remove), ["v18.19.0", "v20.11.0"]);
+        assert_eq!(
+            reasons(&pruned, "v18.19.0"),
+            ["同一主版本已有更新的 v18.20.8"]
+        );
+        assert_eq!(reasons(&pruned, "v22.1.0"), ["不满足任何清理规则"]);
+
+        let pruned = plan(
+            &dir,
+            &policy(Some(2), None, false),
+            &[],
+            &[],
+            SystemTime::now(),
+        );
+        assert!(pruned.remove.is_empty());
+    }
+
+    #[test]
+    fn keeps_versions_referenced_by_aliases_and_projects() {
+        let (temp, dir) = fixture(&INSTALLED);
+        link(
+            &dir.installation_dir("v20.11.0"),
+            &dir.aliases_dir().join("default"),
+        );
+        let project = temp.path().join("app");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "v18.19.0\n").unwrap();
+        let projects = [ProjectEntry {
+            path: project.to_string_lossy().to_string(),
+            added_at: 0,
+        }];
+
+        let pruned = plan(
+            &dir,
+            &policy(Some(1), None, true),
+            &projects,
+            &[],
+            SystemTime::now(),
+        );
+        assert!(pruned.remove.is_empty());
+        assert_eq!(reasons(&pruned, "v20.11.0"), ["被 别名 default 引用"]);
+        assert_eq!(
+            reasons(&pruned, "v18.19.0"),
+            [format!("被 项目 {} 引用", project.display())]
+        );
+
+        let pruned = plan(
+            &dir,
+            &policy(Some(1), None, false),
+            &projects,
+            &[],
+            SystemTime::now(),
+        );
+        assert_eq!(versions(&pruned.remove), ["v18.19.0", "v20.11.0"]);
+    }
+
+    #[test]
+    fn keeps_versions_with_running_processes() {
+        let (_temp, dir) = fixture(&INSTALLED);
+
+        let pruned = plan(
+            &dir,
+            &policy(Some(1), None, false),
+            &[],
+            &[process("v18.19.0")],
+            SystemTime::now(),
+        );
+        assert_eq!(versions(&pruned.remove), ["v20.11.0"]);
+        assert_eq!(reasons(&pruned, "v18.19.0"), ["有 node 进程正在运行"]);
+    }
+
+    #[test]
+    fn removes_unused_versions_only_with_a_session_signal() {
+        let (_temp, dir) = fixture(&INSTALLED);
+        link(
+            &dir.installation_dir("v22.1.0"),
+            &dir.multishell_root().unwrap().join("12345_1700000000000"),
+        );
+        let later = |days: u32| SystemTime::now() + DAY * days;
+
+        let pruned = plan(&dir, &policy(None, Some(30), false), &[], &[], later(40));
+        assert_eq!(versions(&pruned.remove), ["v22.1.0"]);
+        assert_eq!(reasons(&pruned, "v22.1.0"), ["超过 30 天未使用"]);
+        assert!(pruned.remove[0].last_used_at.is_some());
+        // 没有会话链接的版本无法确定使用时间，不按此规则清理
+        assert!(pruned.keep.iter().all(|entry| entry.last_used_at.is_none()));
+
+        let pruned = plan(&dir, &policy(None, Some(30), false), &[], &[], later(1));
+        assert!(pruned.remove.is_empty());
+    }
+
+    #[test]
+    fn refuses_versions_that_are_no_longer_candidates() {
+        let (_temp, dir) = fixture(&INSTALLED);
+        let pruned = plan(
+            &dir,
+            &policy(Some(1), None, false),
+            &[],
+            &[process("v20.11.0")],
+            SystemTime::now(),
+        );
+
+        let requested = ["v18.19.0", "v20.11.0", "v9.0.0", "v18.19.0"];
+        let (confirmed, result) =
+            confirm_candidates(&pruned, requested.iter().map(|v| v.to_string()).collect());
+
+        assert_eq!(confirmed, ["v18.19.0"]);
+        assert!(result.removed.is_empty());
+        assert_eq!(
+            result.failed,
+            [
+                PruneFailure {
+                    version: "v20.11.0".to_string(),
+                    error: "已不满足清理条件：有 node 进程正在运行".to_string(),
+                },
+                PruneFailure {
+                    version: "v9.0.0".to_string(),
+                    error: "版本未安装".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::time::Duration;
+
+/// 官方 Node 下载源
+pub const DEFAULT_NODE_DIST_MIRROR: &str = "https://nodejs.org/dist";
+
+/// 连接镜像的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 两次读取之间的超时时间，避免镜像无响应时一直等待
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// files 中安装包格式的后缀，如 osx-arm64-tar、win-x64-msi
+const PACKAGE_SUFFIXES: &[&str] = &["tar", "zip", "msi", "7z", "pkg", "exe"];
+
+/// 远程 Node 版本信息，对应镜像 index.json 中的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteRelease {
+    /// 版本号，如 v22.21.1
+    pub version: String,
+    /// 发布日期，如 2024-11-20
+    pub date: String,
+    /// LTS 代号，如 "Jod"；非 LTS 版本为 None
+    #[serde(deserialize_with = "deserialize_lts")]
+    pub lts: Option<String>,
+    /// 是否为安全更新版本
+    #[serde(default)]
+    pub security: bool,
+    /// 附带的 npm 版本（早期版本没有此字段）
+    #[serde(default)]
+    pub npm: Option<String>,
+    #[serde(default)]
+    pub v8: Option<String>,
+    #[serde(default)]
+    pub openssl: Option<String>,
+    /// 可用的平台文件列表，如 linux-x64、osx-arm64-tar
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl RemoteRelease {
+    /// files 中是否有该架构的安装包，文件名形如 linux-x64、linux-x64-musl、osx-arm64-tar、win-x86-msi
+    pub fn has_arch(&self, arch: &str) -> bool {
+        self.files
+            .iter()
+            .filter_map(|file| file_arch(file))
+            .any(|a| a.eq_ignore_ascii_case(arch))
+    }
+}
+
+/// 去掉平台前缀和安装包格式后缀后的架构：linux-x64-musl 为 x64-musl，osx-arm64-tar 为 arm64；
+/// headers、src 等不区分架构的条目为 None
+fn file_arch(file: &str) -> Option<&str> {
+    let (_, rest) = file.split_once('-')?;
+    Some(
+        PACKAGE_SUFFIXES
+            .iter()
+            .find_map(|suffix| rest.strip_suffix(suffix)?.strip_suffix('-'))
+            .unwrap_or(rest),
+    )
+}
+
+/// index.json 中 lts 字段为 false 或代号字符串
+fn deserialize_lts<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Lts {
+        Flag(bool),
+        Codename(String),
+    }
+
+    Ok(match Lts::deserialize(deserializer)? {
+        Lts::Codename(name) => Some(name),
+        Lts::Flag(_) => None,
+    })
+}
+
+/// 远程版本列表的筛选选项，lts_only、keyword 与 fnm list-remote 的 --lts / --filter 对应
+#[derive(Debug, Clone, Default)]
+pub struct RemoteFilter {
+    pub lts_only: bool,
+    /// 只保留安全更新版本
+    pub security_only: bool,
+    /// 只保留提供该架构安装包的版本，如 x64、arm64
+    pub arch: Option<String>,
+    /// 版本前缀（如 20、v20.1）或 LTS 代号
+    pub keyword: Option<String>,
+}
+
+impl RemoteFilter {
+    fn matches(&self, release: &RemoteRelease) -> bool {
+        if self.lts_only && release.lts.is_none() {
+            return false;
+        }
+        if self.security_only && !release.security {
+            return false;
+        }
+        if let Some(arch) = self
+            .arch
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+        {
+            if !release.has_arch(arch) {
+                return false;
+            }
+        }
+
+        let keyword = match self.keyword.as_deref().map(str::trim) {
+            Some(keyword) if !keyword.is_empty() => keyword,
+            _ => return true,
+        };
+
+        if let Some(ref codename) = release.lts {
+            if codename.eq_ignore_ascii_case(keyword) {
+                return true;
+            }
+        }
+
+        // 按版本段匹配前缀，避免 "2" 匹配到 v20.x
+        let keyword = keyword.trim_start_matches('v');
+        let version = release.version.trim_start_matches('v');
+        version == keyword || version.starts_with(&format!("{}.", keyword))
+    }
+}
+
+/// 基于镜像 index.json 的远程版本客户端
+pub struct RemoteIndexClient {
+    mirror: String,
+    client: reqwest::Client,
+}
+
+impl RemoteIndexClient {
+    /// 使用指定的镜像地址创建客户端，如 https://npmmirror.com/mirrors/node
+    pub fn new(mirror: &str) -> Self {
+        let mirror = mirror.trim().trim_end_matches('/');
+        RemoteIndexClient {
+            mirror: if mirror.is_empty() {
+                DEFAULT_NODE_DIST_MIRROR.to_string()
+            } else {
+                mirror.to_string()
+            },
+            client: build_client(READ_TIMEOUT),
+        }
+    }
+
+    /// 修改读取超时时间
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.client = build_client(timeout);
+        self
+    }
+
+    /// index.json 的完整地址
+    pub fn index_url(&self) -> String {
+        format!("{}/index.json", self.mirror)
+    }
+
+    /// 获取全部远程版本，按镜像返回的顺序（最新版本在前）
+    pub async fn fetch_releases(&self) -> Result<Vec<RemoteRelease>, FnmGuiError> {
+        let url = self.index_url();
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error(&url, e))?;
+
+        if !response.status().is_success() {
+            return Err(network_error(&url, format!("HTTP {}", response.status())));
+        }
+
+        let body = response.text().await.map_err(|e| network_error(&url, e))?;
+
+        parse_index(&body)
+    }
+
+    /// 获取并按选项筛选远程版本
+    pub async fn list_releases(&self, filter: &RemoteFilter) -> Result<Vec<RemoteRelease>, FnmGuiError> {
+        let releases = self.fetch_releases().await?;
+        Ok(releases.into_iter().filter(|r| filter.matches(r)).collect())
+    }
+}
+
+/// 解析 index.json 内容
+pub fn parse_index(body: &str) -> Result<Vec<RemoteRelease>, FnmGuiError> {
+    serde_json::from_str(body).map_err(|e| FnmGuiError::parse("index.json", e))
+}
+
+fn build_client(read_timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(read_timeout)
+        .build()
+        .unwrap_or_default()
+}
+
+fn network_error(url: &str, message: impl ToString) -> FnmGuiError {
+    FnmGuiError::Network {
+        url: url.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 截取自 nodejs.org/dist/index.json
+    const INDEX_JSON: &str = r#"[
+        {"version":"v23.3.0","date":"2024-11-20","files":["linux-arm64","linux-x64","osx-arm64-tar","osx-x64-tar","win-arm64-zip","win-x64-zip"],"npm":"10.9.0","v8":"12.9.202.28","uv":"1.49.1","zlib":"1.3.0.1-motley-82a5fec","openssl":"3.0.15+quic","modules":"131","lts":false,"security":false},
+        {"version":"v22.11.0","date":"2024-10-29","files":["linux-arm64","linux-x64","osx-arm64-tar","osx-x64-tar","win-x64-zip"],"npm":"10.9.0","v8":"12.4.254.21","openssl":"3.0.15+quic","lts":"Jod","security":false},
+        {"version":"v20.18.1","date":"2024-11-20","files":["aix-ppc64","linux-arm64","linux-armv7l","linux-x64","osx-x64-tar","win-x86-msi"],"npm":"10.8.2","v8":"11.3.244.8","openssl":"3.0.13+quic","lts":"Iron","security":true},
+        {"version":"v0.1.14","date":"2011-08-26","files":["src"],"lts":false}
+    ]"#;
+
+    /// 在 127.0.0.1 上启动只返回 body 的 HTTP 服务，返回镜像地址
+    async fn serve(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        format!("http://{}/dist/", addr)
+    }
+
+    fn versions(releases: &[RemoteRelease]) -> Vec<&str> {
+        releases.iter().map(|r| r.version.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn fetches_and_deserializes_index() {
+        let client = RemoteIndexClient::new(&serve("200 OK", INDEX_JSON).await);
+        assert!(client.index_url().ends_with("/dist/index.json"));
+
+        let releases = client.fetch_releases().await.unwrap();
+        assert_eq!(
+            versions(&releases),
+            ["v23.3.0", "v22.11.0", "v20.18.1", "v0.1.14"]
+        );
+
+        let iron = &releases[2];
+        assert_eq!(iron.lts.as_deref(), Some("Iron"));
+        assert!(iron.security);
+        assert_eq!(iron.npm.as_deref(), Some("10.8.2"));
+        assert_eq!(iron.openssl.as_deref(), Some("3.0.13+quic"));
+        assert_eq!(releases[3].npm, None);
+        assert!(!releases[3].security);
+    }
+
+    #[tokio::test]
+    async fn filters_by_lts_arch_security_and_keyword() {
+        let client = RemoteIndexClient::new(&serve("200 OK", INDEX_JSON).await);
+        let list = |filter: RemoteFilter| {
+            let client = &client;
+            async move { versions(&client.list_releases(&filter).await.unwrap()).join(",") }
+        };
+
+        let lts = RemoteFilter {
+            lts_only: true,
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(lts).await, "v22.11.0,v20.18.1");
+
+        let security = RemoteFilter {
+            security_only: true,
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(security).await, "v20.18.1");
+
+        let armv7l = RemoteFilter {
+            arch: Some("armv7l".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(armv7l).await, "v20.18.1");
+
+        let x86 = RemoteFilter {
+            arch: Some("X86".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(x86).await, "v20.18.1");
+
+        let arm64_lts = RemoteFilter {
+            lts_only: true,
+            arch: Some("arm64".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(arm64_lts).await, "v22.11.0,v20.18.1");
+
+        let codename = RemoteFilter {
+            keyword: Some("jod".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(codename).await, "v22.11.0");
+
+        // 按版本段匹配，"2" 不匹配 v20.x、v22.x、v23.x
+        let prefix = RemoteFilter {
+            keyword: Some("2".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(prefix).await, "");
+
+        let minor = RemoteFilter {
+            keyword: Some("v20.18".to_string()),
+            ..RemoteFilter::default()
+        };
+        assert_eq!(list(minor).await, "v20.18.1");
+    }
+
+    #[tokio::test]
+    async fn reports_http_errors_as_network_errors() {
+        let client = RemoteIndexClient::new(&serve("404 Not Found", "not found").await);
+        let err = client.fetch_releases().await.unwrap_err();
+        assert_eq!(err.code(), "network");
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[test]
+    fn matches_arch_with_libc_and_package_suffixes() {
+        let release = |files: &[&str]| RemoteRelease {
+            version: "v22.11.0".to_string(),
+            date: "2024-10-29".to_string(),
+            lts: None,
+            security: false,
+            npm: None,
+            v8: None,
+            openssl: None,
+            files: files.iter().map(|f| f.to_string()).collect(),
+        };
+
+        let musl = release(&["headers", "linux-x64-musl", "linux-arm64-musl", "src"]);
+        assert!(musl.has_arch("x64-musl"));
+        assert!(musl.has_arch("ARM64-musl"));
+        assert!(!musl.has_arch("x64"));
+        assert!(!musl.has_arch("musl"));
+
+        let official = release(&["linux-x64", "osx-arm64-tar", "win-x86-msi", "win-arm64-7z", "osx-x64-pkg"]);
+        for arch in ["x64", "arm64", "x86"] {
+            assert!(official.has_arch(arch), "{arch}");
+        }
+        assert!(!official.has_arch("x64-musl"));
+        assert!(!official.has_arch("tar"));
+        assert!(!release(&["headers", "src"]).has_arch("src"));
+    }
+
+    #[tokio::test]
+    async fn times_out_on_a_stalled_mirror() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // 接受连接后不返回任何内容
+            let connection = listener.accept().await;
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(connection);
+        });
+
+        let client = RemoteIndexClient::new(&format!("http://{}/dist", addr))
+            .with_read_timeout(Duration::from_millis(200));
+        let err = tokio::time::timeout(Duration::from_secs(5), client.fetch_releases())
+            .await
+            .expect("读取超时未生效")
+            .unwrap_err();
+        assert_eq!(err.code(), "network");
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_index_as_parse_error() {
+        let client = RemoteIndexClient::new(&serve("200 OK", "<html></html>").await);
+        let err = client.fetch_releases().await.unwrap_err();
+        assert_eq!(err.code(), "parse");
+    }
+}
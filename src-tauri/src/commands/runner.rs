@@ -0,0 +1,283 @@
+use super::common::create_fnm_command;
+use super::fnm_dir::fnm_dir_path;
+use crate::error::FnmGuiError;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::{Command, Output, Stdio};
+#[cfg(any(test, feature = "test-support"))]
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// 命令执行结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// 退出码，被信号终止时为 None
+    pub code: Option<i32>,
+}
+
+impl CommandOutput {
+    /// 以退出码 0 结束并输出 stdout
+    pub fn success(stdout: impl Into<String>) -> Self {
+        CommandOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            code: Some(0),
+        }
+    }
+
+    /// 以指定退出码结束并输出 stderr
+    pub fn failure(code: i32, stderr: impl Into<String>) -> Self {
+        CommandOutput {
+            stdout: String::new(),
+            stderr: stderr.into(),
+            code: Some(code),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl From<Output> for CommandOutput {
+    fn from(output: Output) -> Self {
+        CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            code: output.status.code(),
+        }
+    }
+}
+
+/// stream 返回的 future，被丢弃时终止 fnm 进程
+pub type StreamFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<CommandOutput, FnmGuiError>> + Send + 'a>>;
+
+/// 执行 fnm 的抽象，commands 通过它调用 fnm，便于在测试中替换为脚本化的实现
+pub trait FnmRunner: Send + Sync {
+    /// fnm 目录，用于读取别名和版本目录
+    fn fnm_dir(&self) -> Result<PathBuf, FnmGuiError>;
+
+    /// 执行 fnm 并等待结束，envs 为额外设置的环境变量
+    fn run_with_env(
+        &self,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<CommandOutput, FnmGuiError>;
+
+    /// 执行 fnm 并等待结束
+    fn run(&self, args: &[&str]) -> Result<CommandOutput, FnmGuiError> {
+        self.run_with_env(args, &[])
+    }
+
+    /// 执行 fnm 并逐行回调 stdout 和 stderr 中的非空行，用于需要流式读取输出的场景（如安装）
+    fn stream<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        on_line: &'a (dyn Fn(&str) + Sync),
+    ) -> StreamFuture<'a>;
+}
+
+/// 调用真实 fnm 可执行文件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessRunner;
+
+impl ProcessRunner {
+    fn command(args: &[&str]) -> Result<Command, FnmGuiError> {
+        let mut cmd = create_fnm_command()?;
+        cmd.args(args);
+        Ok(cmd)
+    }
+}
+
+impl FnmRunner for ProcessRunner {
+    fn fnm_dir(&self) -> Result<PathBuf, FnmGuiError> {
+        fnm_dir_path()
+    }
+
+    fn run_with_env(
+        &self,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<CommandOutput, FnmGuiError> {
+        ProcessRunner::command(args)?
+            .envs(envs.iter().copied())
+            .output()
+            .map(CommandOutput::from)
+            .map_err(|e| FnmGuiError::spawn(describe(args), e))
+    }
+
+    fn stream<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        on_line: &'a (dyn Fn(&str) + Sync),
+    ) -> StreamFuture<'a> {
+        Box::pin(async move {
+            let mut child = tokio::process::Command::from(ProcessRunner::command(args)?)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| FnmGuiError::spawn(describe(args), e))?;
+
+            let (stdout, stderr) = tokio::join!(
+                read_lines(child.stdout.take(), on_line),
+                read_lines(child.stderr.take(), on_line)
+            );
+            let status = child
+                .wait()
+                .await
+                .map_err(|e| FnmGuiError::spawn(describe(args), e))?;
+
+            Ok(CommandOutput {
+                stdout,
+                stderr,
+                code: status.code(),
+            })
+        })
+    }
+}
+
+/// 逐行读取输出，返回读取到的全部内容
+async fn read_lines<R>(reader: Option<R>, on_line: &(dyn Fn(&str) + Sync)) -> String
+where
+    R: AsyncRead + Unpin,
+{
+    let mut collected = String::new();
+    let Some(reader) = reader else {
+        return collected;
+    };
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        on_line(line);
+        collected.push_str(line);
+        collected.push('\n');
+    }
+
+    collected
+}
+
+/// 按预设脚本返回结果的 fnm，记录每次调用的参数，不启动任何进程
+/// 响应按注册顺序匹配参数前缀，如 ["install"] 可匹配 ["install", "20"]
+#[cfg(any(test, feature = "test-support"))]
+pub struct ScriptedRunner {
+    fnm_dir: PathBuf,
+    responses: Vec<(Vec<String>, CommandOutput)>,
+    invocations: Mutex<Vec<Vec<String>>>,
+    unavailable: bool,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl ScriptedRunner {
+    pub fn new(fnm_dir: impl Into<PathBuf>) -> Self {
+        ScriptedRunner {
+            fnm_dir: fnm_dir.into(),
+            responses: Vec::new(),
+            invocations: Mutex::new(Vec::new()),
+            unavailable: false,
+        }
+    }
+
+    /// 注册参数以 args 开头时返回的结果
+    pub fn on(mut self, args: &[&str], output: CommandOutput) -> Self {
+        let args = args.iter().map(|a| a.to_string()).collect();
+        self.responses.push((args, output));
+        self
+    }
+
+    /// 模拟未安装 fnm：每次调用都返回 FnmNotFound
+    pub fn unavailable(mut self) -> Self {
+        self.unavailable = true;
+        self
+    }
+
+    /// 至今为止的全部调用参数
+    pub fn invocations(&self) -> Vec<Vec<String>> {
+        self.invocations.lock().unwrap().clone()
+    }
+
+    /// 记录调用并查找对应的结果，没有匹配项时视为 fnm 报错退出
+    fn respond(&self, args: &[&str]) -> Result<CommandOutput, FnmGuiError> {
+        self.invocations
+            .lock()
+            .unwrap()
+            .push(args.iter().map(|a| a.to_string()).collect());
+
+        if self.unavailable {
+            return Err(FnmGuiError::FnmNotFound);
+        }
+
+        Ok(self
+            .responses
+            .iter()
+            .find(|(prefix, _)| {
+                prefix.len() <= args.len() && prefix.iter().zip(args).all(|(p, a)| p == a)
+            })
+            .map(|(_, output)| output.clone())
+            .unwrap_or_else(|| {
+                CommandOutput::failure(2, format!("没有为 {} 预设结果", describe(args)))
+            }))
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl FnmRunner for ScriptedRunner {
+    fn fnm_dir(&self) -> Result<PathBuf, FnmGuiError> {
+        Ok(self.fnm_dir.clone())
+    }
+
+    fn run_with_env(
+        &self,
+        args: &[&str],
+        _envs: &[(&str, &str)],
+    ) -> Result<CommandOutput, FnmGuiError> {
+        self.respond(args)
+    }
+
+    /// 按 stdout、stderr 的顺序回调预设输出中的非空行
+    fn stream<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        on_line: &'a (dyn Fn(&str) + Sync),
+    ) -> StreamFuture<'a> {
+        let output = self.respond(args);
+        Box::pin(async move {
+            let output = output?;
+            output
+                .stdout
+                .lines()
+                .chain(output.stderr.lines())
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .for_each(on_line);
+            Ok(output)
+        })
+    }
+}
+
+/// 用于错误信息的命令描述，如 "fnm install"
+pub fn describe(args: &[&str]) -> String {
+    match args.first() {
+        Some(sub) => format!("fnm {}", sub),
+        None => "fnm".to_string(),
+    }
+}
+
+/// 在阻塞线程池中执行 fnm 调用等阻塞操作，避免卡住窗口
+pub async fn run_blocking<T, F>(f: F) -> Result<T, FnmGuiError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, FnmGuiError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| FnmGuiError::Internal(e.to_string()))?
+}
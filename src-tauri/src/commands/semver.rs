@@ -0,0 +1,746 @@
+use super::env::read_fnm_env;
+use super::fnm_layout::FnmDir;
+use super::project_version::resolve_installed;
+use super::remote::{RemoteIndexClient, RemoteRelease};
+use super::runner::{run_blocking, ProcessRunner};
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use tauri::command;
+
+/// 预发布版本的一个标识，如 v22.0.0-rc.1 中的 "rc" 和 1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreRelease {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Ord for PreRelease {
+    /// 数字标识小于字母标识，同类之间按数值或字典序比较
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (PreRelease::Numeric(a), PreRelease::Numeric(b)) => a.cmp(b),
+            (PreRelease::Alpha(a), PreRelease::Alpha(b)) => a.cmp(b),
+            (PreRelease::Numeric(_), PreRelease::Alpha(_)) => Ordering::Less,
+            (PreRelease::Alpha(_), PreRelease::Numeric(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for PreRelease {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 完整的 Node 版本号，按 semver 规则比较：预发布版本低于同号的正式版本
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<PreRelease>,
+}
+
+impl NodeVersion {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        NodeVersion {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+
+    /// 解析 v20.12.2、20.12.2、v22.0.0-rc.1 等写法，忽略 + 之后的构建信息
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(version)
+            .split('+')
+            .next()?;
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, parse_pre(pre)?),
+            None => (version, Vec::new()),
+        };
+
+        let mut parts = core.split('.').map(parse_number);
+        let (Some(major), Some(minor), Some(patch), None) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next())
+        else {
+            return None;
+        };
+
+        Some(NodeVersion {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn triple(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl Ord for NodeVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.triple().cmp(&other.triple()).then_with(|| {
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for NodeVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for NodeVersion {
+    /// 与 fnm 目录名一致，带 v 前缀
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        for (i, id) in self.pre.iter().enumerate() {
+            f.write_str(if i == 0 { "-" } else { "." })?;
+            match id {
+                PreRelease::Numeric(n) => write!(f, "{}", n)?,
+                PreRelease::Alpha(s) => f.write_str(s)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 不含前导零的十进制数字
+fn parse_number(part: &str) -> Option<u64> {
+    let valid = !part.is_empty()
+        && part.chars().all(|c| c.is_ascii_digit())
+        && (part == "0" || !part.starts_with('0'));
+    if valid {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn parse_pre(pre: &str) -> Option<Vec<PreRelease>> {
+    pre.split('.')
+        .map(|id| {
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                None
+            } else if let Some(n) = parse_number(id) {
+                Some(PreRelease::Numeric(n))
+            } else if id.chars().all(|c| c.is_ascii_digit()) {
+                // 带前导零的数字标识不合法
+                None
+            } else {
+                Some(PreRelease::Alpha(id.to_string()))
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: NodeVersion,
+}
+
+impl Comparator {
+    fn new(op: Op, version: NodeVersion) -> Self {
+        Comparator { op, version }
+    }
+
+    fn matches(&self, version: &NodeVersion) -> bool {
+        let ordering = version.cmp(&self.version);
+        match self.op {
+            Op::Eq => ordering.is_eq(),
+            Op::Gt => ordering.is_gt(),
+            Op::Gte => ordering.is_ge(),
+            Op::Lt => ordering.is_lt(),
+            Op::Lte => ordering.is_le(),
+        }
+    }
+}
+
+/// 不完整的版本号，缺省或 x、* 的部分为 None
+#[derive(Debug, Clone, Copy)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+/// npm 风格的版本范围，如 "20"、"^18.17"、">=18 <21"、"16 || 18"、"1.2.3 - 2.x"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    /// 以 || 分隔的各组条件，满足任意一组即可
+    sets: Vec<Vec<Comparator>>,
+}
+
+impl VersionReq {
+    pub fn parse(req: &str) -> Option<Self> {
+        let req = req.trim();
+        if req.is_empty() {
+            return None;
+        }
+        let sets = req.split("||").map(parse_set).collect::<Option<Vec<_>>>()?;
+        Some(VersionReq { sets })
+    }
+
+    /// 预发布版本只有在同一组条件中出现了同号的预发布版本时才匹配，与 npm 一致
+    pub fn matches(&self, version: &NodeVersion) -> bool {
+        self.sets.iter().any(|set| {
+            set.iter().all(|c| c.matches(version))
+                && (!version.is_prerelease()
+                    || set.iter().any(|c| {
+                        c.version.is_prerelease() && c.version.triple() == version.triple()
+                    }))
+        })
+    }
+
+    /// 候选版本中满足范围的最高版本，无法解析的候选会被忽略
+    pub fn max_satisfying<'a, I>(&self, candidates: I) -> Option<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        candidates
+            .into_iter()
+            .filter_map(|candidate| Some((NodeVersion::parse(candidate)?, candidate)))
+            .filter(|(version, _)| self.matches(version))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+fn parse_set(set: &str) -> Option<Vec<Comparator>> {
+    let set = set.trim();
+    if let Some((from, to)) = set.split_once(" - ") {
+        return parse_hyphen(from.trim(), to.trim());
+    }
+
+    // 运算符与版本号之间允许有空格，如 ">= 18"
+    let mut tokens = Vec::new();
+    let mut pending_op = String::new();
+    for token in set.split_whitespace() {
+        if token
+            .chars()
+            .all(|c| matches!(c, '<' | '>' | '=' | '^' | '~'))
+        {
+            pending_op.push_str(token);
+        } else {
+            tokens.push(format!("{}{}", std::mem::take(&mut pending_op), token));
+        }
+    }
+    if !pending_op.is_empty() {
+        return None;
+    }
+
+    let mut comparators = Vec::new();
+    for token in tokens {
+        comparators.extend(parse_comparator(&token)?);
+    }
+    Some(comparators)
+}
+
+fn parse_hyphen(from: &str, to: &str) -> Option<Vec<Comparator>> {
+    let (lower, lower_pre) = parse_partial(from)?;
+    let (upper, upper_pre) = parse_partial(to)?;
+    let mut comparators = Vec::new();
+
+    if lower.major.is_some() {
+        comparators.push(Comparator::new(Op::Gte, floor(lower, lower_pre)));
+    }
+    match upper {
+        Partial {
+            major: Some(major),
+            minor: Some(minor),
+            patch: Some(patch),
+        } => comparators.push(Comparator::new(
+            Op::Lte,
+            NodeVersion {
+                pre: upper_pre,
+                ..NodeVersion::new(major, minor, patch)
+            },
+        )),
+        _ => comparators.extend(next_ceiling(upper)),
+    }
+    Some(comparators)
+}
+
+fn parse_comparator(token: &str) -> Option<Vec<Comparator>> {
+    let (op, rest) = [">=", "<=", "~>", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|op| token.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("", token));
+    let (partial, pre) = parse_partial(rest)?;
+    let full = partial.patch.is_some();
+
+    let comparators = match op {
+        "" | "=" if full => vec![Comparator::new(Op::Eq, floor(partial, pre))],
+        "" | "=" => x_range(partial),
+        "^" => match partial {
+            Partial { major: None, .. } => Vec::new(),
+            Partial {
+                major: Some(0),
+                minor: Some(0),
+                patch: Some(patch),
+            } => vec![
+                Comparator::new(Op::Gte, floor(partial, pre)),
+                Comparator::new(Op::Lt, NodeVersion::new(0, 0, patch + 1)),
+            ],
+            Partial {
+                major: Some(0),
+                minor: Some(minor),
+                ..
+            } => vec![
+                Comparator::new(Op::Gte, floor(partial, pre)),
+                Comparator::new(Op::Lt, NodeVersion::new(0, minor + 1, 0)),
+            ],
+            Partial {
+                major: Some(major), ..
+            } => vec![
+                Comparator::new(Op::Gte, floor(partial, pre)),
+                Comparator::new(Op::Lt, NodeVersion::new(major + 1, 0, 0)),
+            ],
+        },
+        "~" | "~>" => match partial {
+            Partial { major: None, .. } => Vec::new(),
+            Partial {
+                major: Some(major),
+                minor: Some(minor),
+                ..
+            } => vec![
+                Comparator::new(Op::Gte, floor(partial, pre)),
+                Comparator::new(Op::Lt, NodeVersion::new(major, minor + 1, 0)),
+            ],
+            _ => x_range(partial),
+        },
+        ">" if full => vec![Comparator::new(Op::Gt, floor(partial, pre))],
+        // >20 等价于 >=21.0.0，>* 不匹配任何版本
+        ">" => match next_ceiling(partial) {
+            Some(ceiling) => vec![Comparator::new(Op::Gte, ceiling.version)],
+            None => vec![nothing()],
+        },
+        ">=" if partial.major.is_none() => Vec::new(),
+        ">=" => vec![Comparator::new(Op::Gte, floor(partial, pre))],
+        "<" if partial.major.is_none() => vec![nothing()],
+        "<" => vec![Comparator::new(Op::Lt, floor(partial, pre))],
+        "<=" if full => vec![Comparator::new(Op::Lte, floor(partial, pre))],
+        // <=20 等价于 <21.0.0
+        "<=" => next_ceiling(partial).into_iter().collect(),
+        _ => return None,
+    };
+    Some(comparators)
+}
+
+/// 解析可能不完整的版本号，返回各部分和预发布标识（只有完整版本号可以带预发布标识）
+fn parse_partial(version: &str) -> Option<(Partial, Vec<PreRelease>)> {
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let version = version.split('+').next()?;
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, parse_pre(pre)?),
+        None => (version, Vec::new()),
+    };
+
+    let mut parts = Vec::new();
+    for part in core.split('.') {
+        parts.push(match part {
+            "x" | "X" | "*" => None,
+            _ => Some(parse_number(part)?),
+        });
+    }
+    if parts.len() > 3 {
+        return None;
+    }
+    parts.resize(3, None);
+
+    // 通配符之后的部分都视为通配符
+    let wildcard = parts.iter().position(Option::is_none).unwrap_or(3);
+    parts[wildcard..].iter_mut().for_each(|part| *part = None);
+    if wildcard < 3 && !pre.is_empty() {
+        return None;
+    }
+
+    Some((
+        Partial {
+            major: parts[0],
+            minor: parts[1],
+            patch: parts[2],
+        },
+        pre,
+    ))
+}
+
+/// 不完整版本号的下限，缺省部分补 0
+fn floor(partial: Partial, pre: Vec<PreRelease>) -> NodeVersion {
+    NodeVersion {
+        pre,
+        ..NodeVersion::new(
+            partial.major.unwrap_or(0),
+            partial.minor.unwrap_or(0),
+            partial.patch.unwrap_or(0),
+        )
+    }
+}
+
+/// 不完整版本号之后的第一个版本：20 之后为 21.0.0，20.1 之后为 20.2.0
+fn next_ceiling(partial: Partial) -> Option<Comparator> {
+    let ceiling = match partial {
+        Partial { major: None, .. } => return None,
+        Partial {
+            major: Some(major),
+            minor: None,
+            ..
+        } => NodeVersion::new(major + 1, 0, 0),
+        Partial {
+            major: Some(major),
+            minor: Some(minor),
+            patch: None,
+        } => NodeVersion::new(major, minor + 1, 0),
+        Partial {
+            major: Some(major),
+            minor: Some(minor),
+            patch: Some(patch),
+        } => NodeVersion::new(major, minor, patch + 1),
+    };
+    Some(Comparator::new(Op::Lt, ceiling))
+}
+
+/// 20、20.x、* 等通配写法
+fn x_range(partial: Partial) -> Vec<Comparator> {
+    match next_ceiling(partial) {
+        Some(ceiling) => vec![
+            Comparator::new(Op::Gte, floor(partial, Vec::new())),
+            ceiling,
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// 不匹配任何版本的条件
+fn nothing() -> Comparator {
+    Comparator::new(
+        Op::Lt,
+        NodeVersion {
+            pre: vec![PreRelease::Numeric(0)],
+            ..NodeVersion::new(0, 0, 0)
+        },
+    )
+}
+
+/// fnm 接受的版本写法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSpec {
+    /// latest：最新的正式版本
+    Latest,
+    /// lts/* 为 None，lts/<codename> 为小写的代号
+    Lts(Option<String>),
+    /// 完整版本号、部分版本号或范围
+    Range(VersionReq),
+    /// 其他名称按别名处理
+    Alias(String),
+}
+
+impl VersionSpec {
+    pub fn parse(spec: &str) -> Self {
+        let spec = spec.trim();
+        let lower = spec.to_lowercase();
+
+        if lower == "latest" {
+            return VersionSpec::Latest;
+        }
+        if let Some(codename) = lower.strip_prefix("lts/") {
+            return VersionSpec::Lts((codename != "*").then(|| codename.to_string()));
+        }
+        match VersionReq::parse(spec) {
+            Some(req) => VersionSpec::Range(req),
+            None => VersionSpec::Alias(spec.to_string()),
+        }
+    }
+
+    /// 在已安装的版本中选出匹配的最高版本（latest 与范围）
+    /// lts 和别名需要查找 fnm 的别名目录，由调用方处理
+    pub fn max_installed<'a, I>(&self, installed: I) -> Option<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        match self {
+            VersionSpec::Latest => latest().max_satisfying(installed),
+            VersionSpec::Range(req) => req.max_satisfying(installed),
+            VersionSpec::Lts(_) | VersionSpec::Alias(_) => None,
+        }
+    }
+
+    /// 在远程版本列表中选出匹配的最高版本，别名没有对应的远程版本
+    pub fn resolve_remote<'a>(&self, releases: &'a [RemoteRelease]) -> Option<&'a RemoteRelease> {
+        let req = match self {
+            VersionSpec::Range(req) => req.clone(),
+            VersionSpec::Alias(_) => return None,
+            VersionSpec::Latest | VersionSpec::Lts(_) => latest(),
+        };
+
+        releases
+            .iter()
+            .filter(|release| match self {
+                VersionSpec::Lts(None) => release.lts.is_some(),
+                VersionSpec::Lts(Some(codename)) => release
+                    .lts
+                    .as_deref()
+                    .is_some_and(|lts| lts.eq_ignore_ascii_case(codename)),
+                _ => true,
+            })
+            .filter_map(|release| Some((NodeVersion::parse(&release.version)?, release)))
+            .filter(|(version, _)| req.matches(version))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, release)| release)
+    }
+
+    pub fn kind(&self) -> SpecKind {
+        match self {
+            VersionSpec::Latest => SpecKind::Latest,
+            VersionSpec::Lts(_) => SpecKind::Lts,
+            VersionSpec::Range(_) => SpecKind::Range,
+            VersionSpec::Alias(_) => SpecKind::Alias,
+        }
+    }
+}
+
+/// 匹配所有正式版本
+fn latest() -> VersionReq {
+    VersionReq {
+        sets: vec![Vec::new()],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpecKind {
+    Latest,
+    Lts,
+    Range,
+    Alias,
+}
+
+/// 版本写法的解析结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionResolution {
+    pub spec: String,
+    pub kind: SpecKind,
+    /// 匹配的已安装版本
+    pub installed: Option<String>,
+    /// 匹配的远程最新版本，未查询远程时为 None
+    pub remote: Option<String>,
+}
+
+/// 将 20、lts/iron、latest、">=18 <21" 等写法解析为具体版本
+/// remote 为 true 时同时查询远程版本列表
+#[command]
+pub async fn resolve_version_spec(
+    spec: String,
+    remote: Option<bool>,
+) -> Result<VersionResolution, FnmGuiError> {
+    let parsed = VersionSpec::parse(&spec);
+    let installed = {
+        let spec = spec.clone();
+        run_blocking(move || Ok(resolve_installed(&spec, &FnmDir::current()?))).await?
+    };
+
+    let remote = if remote.unwrap_or(false) {
+        let env = run_blocking(|| read_fnm_env(&ProcessRunner)).await?;
+        let releases = RemoteIndexClient::new(&env.node_dist_mirror)
+            .fetch_releases()
+            .await?;
+        parsed
+            .resolve_remote(&releases)
+            .map(|release| release.version.clone())
+    } else {
+        None
+    };
+
+    Ok(VersionResolution {
+        kind: parsed.kind(),
+        spec,
+        installed,
+        remote,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// 取值范围较小，使生成的版本之间经常出现相同的部分
+    fn small_version() -> impl Strategy<Value = NodeVersion> {
+        let id = prop_oneof![
+            (0..3u64).prop_map(PreRelease::Numeric),
+            prop::sample::select(vec!["alpha", "beta", "rc"])
+                .prop_map(|s| PreRelease::Alpha(s.to_string())),
+        ];
+        let pre = prop_oneof![
+            2 => Just(Vec::new()),
+            1 => prop::collection::vec(id, 1..3),
+        ];
+        (0..4u64, 0..4u64, 0..4u64, pre).prop_map(|(major, minor, patch, pre)| NodeVersion {
+            pre,
+            ..NodeVersion::new(major, minor, patch)
+        })
+    }
+
+    fn prerelease_version() -> impl Strategy<Value = NodeVersion> {
+        small_version().prop_filter("预发布版本", NodeVersion::is_prerelease)
+    }
+
+    fn release(version: NodeVersion) -> NodeVersion {
+        NodeVersion::new(version.major, version.minor, version.patch)
+    }
+
+    /// 比较运算符加上可能不完整的版本号，如 "^1.2"、">=0.3.1-rc.0"、"2.x"
+    fn comparator(with_pre: bool) -> impl Strategy<Value = String> {
+        let op = prop::sample::select(vec!["", "=", ">", ">=", "<", "<=", "^", "~"]);
+        let partial = prop_oneof![
+            small_version().prop_map(move |v| if with_pre { v } else { release(v) }.to_string()),
+            (0..4u64, 0..4u64).prop_map(|(major, minor)| format!("{}.{}", major, minor)),
+            (0..4u64).prop_map(|major| major.to_string()),
+            (0..4u64).prop_map(|major| format!("{}.x", major)),
+            Just("*".to_string()),
+        ];
+        (op, partial).prop_map(|(op, partial)| format!("{}{}", op, partial))
+    }
+
+    /// 以空格连接的一组条件
+    fn range(with_pre: bool) -> impl Strategy<Value = String> {
+        prop::collection::vec(comparator(with_pre), 1..4).prop_map(|set| set.join(" "))
+    }
+
+    fn req(range: &str) -> VersionReq {
+        VersionReq::parse(range).unwrap_or_else(|| panic!("无法解析 {:?}", range))
+    }
+
+    proptest! {
+        #[test]
+        fn ordering_is_total_and_consistent(
+            a in small_version(),
+            b in small_version(),
+            c in small_version(),
+        ) {
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            prop_assert_eq!(a.cmp(&b).is_eq(), a == b);
+            prop_assert_eq!(a.partial_cmp(&b), Some(a.cmp(&b)));
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+            // 预发布版本低于同号的正式版本
+            if a.is_prerelease() {
+                prop_assert!(a < release(a.clone()));
+            }
+        }
+
+        #[test]
+        fn display_round_trips(
+            major in any::<u64>(),
+            minor in any::<u64>(),
+            patch in any::<u64>(),
+            pre in prop::collection::vec(
+                prop_oneof![
+                    any::<u64>().prop_map(PreRelease::Numeric),
+                    "[a-zA-Z-][a-zA-Z0-9-]{0,8}".prop_map(PreRelease::Alpha),
+                ],
+                0..4,
+            ),
+        ) {
+            let version = NodeVersion { pre, ..NodeVersion::new(major, minor, patch) };
+            prop_assert_eq!(NodeVersion::parse(&version.to_string()), Some(version));
+        }
+
+        #[test]
+        fn caret_matches_desugared_range(base in small_version(), v in small_version()) {
+            let (x, y, z) = base.triple();
+            let cases = [
+                (format!("^{}.{}.{}", x, y, z), match (x, y) {
+                    (0, 0) => format!(">=0.0.{} <0.0.{}", z, z + 1),
+                    (0, _) => format!(">=0.{}.{} <0.{}.0", y, z, y + 1),
+                    _ => format!(">={}.{}.{} <{}.0.0", x, y, z, x + 1),
+                }),
+                (format!("^{}.{}", x, y), match x {
+                    0 => format!(">=0.{}.0 <0.{}.0", y, y + 1),
+                    _ => format!(">={}.{}.0 <{}.0.0", x, y, x + 1),
+                }),
+                (format!("^{}", x), format!(">={}.0.0 <{}.0.0", x, x + 1)),
+            ];
+            for (caret, desugared) in cases {
+                prop_assert_eq!(
+                    req(&caret).matches(&v),
+                    req(&desugared).matches(&v),
+                    "{} / {}",
+                    caret,
+                    desugared
+                );
+            }
+        }
+
+        #[test]
+        fn tilde_matches_desugared_range(base in small_version(), v in small_version()) {
+            let (x, y, z) = base.triple();
+            let cases = [
+                (format!("~{}.{}.{}", x, y, z), format!(">={}.{}.{} <{}.{}.0", x, y, z, x, y + 1)),
+                (format!("~{}.{}", x, y), format!(">={}.{}.0 <{}.{}.0", x, y, x, y + 1)),
+                (format!("~{}", x), format!(">={}.0.0 <{}.0.0", x, x + 1)),
+            ];
+            for (tilde, desugared) in cases {
+                prop_assert_eq!(
+                    req(&tilde).matches(&v),
+                    req(&desugared).matches(&v),
+                    "{} / {}",
+                    tilde,
+                    desugared
+                );
+            }
+        }
+
+        #[test]
+        fn or_is_union(a in range(true), b in range(true), v in small_version()) {
+            let union = req(&format!("{} || {}", a, b));
+            prop_assert_eq!(union.matches(&v), req(&a).matches(&v) || req(&b).matches(&v));
+        }
+
+        #[test]
+        fn prerelease_needs_prerelease_comparator(r in range(false), v in prerelease_version()) {
+            prop_assert!(!req(&r).matches(&v), "{} 匹配了 {}", r, v);
+        }
+
+        #[test]
+        fn prerelease_matches_only_same_tuple(
+            base in prerelease_version(),
+            v in prerelease_version(),
+        ) {
+            let req = req(&format!(">={}", base));
+            let expected = base.triple() == v.triple() && v >= base;
+            prop_assert_eq!(req.matches(&v), expected, ">={} / {}", base, v);
+        }
+    }
+}
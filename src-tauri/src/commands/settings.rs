@@ -0,0 +1,314 @@
+use super::project_version::VersionSource;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// 设置文件名，位于应用配置目录下
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 设置文件无法解析时的备份文件名，避免之后保存设置时覆盖用户原有的内容
+const SETTINGS_BACKUP_FILE: &str = "settings.json.bak";
+
+/// fnm 支持的 FNM_VERSION_FILE_STRATEGY 取值
+pub const VERSION_FILE_STRATEGIES: &[&str] = &["local", "recursive"];
+
+/// fnm 支持的 FNM_LOGLEVEL 取值
+pub const LOG_LEVELS: &[&str] = &["quiet", "error", "info"];
+
+/// fnm 支持的 FNM_ARCH 取值
+pub const ARCHES: &[&str] = &["x86", "x64", "x64-musl", "arm64", "armv7l", "ppc64le", "ppc64", "s390x"];
+
+/// 应用内设置的 fnm 配置覆盖值，未设置的项沿用 fnm 自身的配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FnmEnvOverrides {
+    /// fnm 目录，优先于其他来源，见 resolve_fnm_dir
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fnm_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_dist_mirror: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_file_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_engines: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loglevel: Option<String>,
+}
+
+impl FnmEnvOverrides {
+    /// 转换为传给 fnm 的环境变量
+    pub fn to_env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+
+        if let Some(ref dir) = self.fnm_dir {
+            vars.push(("FNM_DIR", dir.clone()));
+        }
+        if let Some(ref mirror) = self.node_dist_mirror {
+            vars.push(("FNM_NODE_DIST_MIRROR", mirror.clone()));
+        }
+        if let Some(ref strategy) = self.version_file_strategy {
+            vars.push(("FNM_VERSION_FILE_STRATEGY", strategy.clone()));
+        }
+        if let Some(engines) = self.resolve_engines {
+            vars.push(("FNM_RESOLVE_ENGINES", engines.to_string()));
+        }
+        if let Some(ref arch) = self.arch {
+            vars.push(("FNM_ARCH", arch.clone()));
+        }
+        if let Some(ref loglevel) = self.loglevel {
+            vars.push(("FNM_LOGLEVEL", loglevel.clone()));
+        }
+
+        vars
+    }
+
+    /// 已设置覆盖值的环境变量名
+    pub fn keys(&self) -> Vec<&'static str> {
+        self.to_env_vars().into_iter().map(|(key, _)| key).collect()
+    }
+
+    /// 去掉空字符串，视为未设置
+    fn normalized(self) -> Self {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        FnmEnvOverrides {
+            fnm_dir: non_empty(self.fnm_dir),
+            node_dist_mirror: non_empty(self.node_dist_mirror),
+            version_file_strategy: non_empty(self.version_file_strategy),
+            resolve_engines: self.resolve_engines,
+            arch: non_empty(self.arch),
+            loglevel: non_empty(self.loglevel),
+        }
+    }
+
+    /// 检查取值是否为 fnm 支持的值，避免写入 fnm 无法识别的配置
+    fn validate(&self) -> Result<(), FnmGuiError> {
+        let check = |name: &str, value: &Option<String>, allowed: &[&str]| match value {
+            Some(v) if !allowed.contains(&v.as_str()) => Err(FnmGuiError::InvalidInput(format!(
+                "{} 不支持 {}，可选值: {}",
+                name,
+                v,
+                allowed.join(", ")
+            ))),
+            _ => Ok(()),
+        };
+
+        check("FNM_VERSION_FILE_STRATEGY", &self.version_file_strategy, VERSION_FILE_STRATEGIES)?;
+        check("FNM_LOGLEVEL", &self.loglevel, LOG_LEVELS)?;
+        check("FNM_ARCH", &self.arch, ARCHES)
+    }
+}
+
+/// 旧版本的清理策略：满足任一删除规则、且不受保留规则保护的版本会被清理
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrunePolicy {
+    /// 每个主版本保留最新的 N 个补丁版本，None 表示不按此规则清理
+    pub keep_latest_per_major: Option<u32>,
+    /// 超过 N 天未使用的版本会被清理，None 表示不按此规则清理
+    pub remove_unused_after_days: Option<u32>,
+    /// 保留被别名或已记录的项目引用的版本
+    pub keep_referenced: bool,
+}
+
+impl PrunePolicy {
+    const DEFAULT: PrunePolicy = PrunePolicy {
+        keep_latest_per_major: Some(1),
+        remove_unused_after_days: None,
+        keep_referenced: true,
+    };
+}
+
+impl Default for PrunePolicy {
+    fn default() -> Self {
+        PrunePolicy::DEFAULT
+    }
+}
+
+/// 持久化的应用设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub fnm_env: FnmEnvOverrides,
+    pub prune_policy: PrunePolicy,
+    /// 项目版本来源的优先级，None 时使用默认顺序
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_sources: Option<Vec<VersionSource>>,
+}
+
+struct SettingsState {
+    path: Option<PathBuf>,
+    settings: AppSettings,
+}
+
+static SETTINGS: Mutex<SettingsState> = Mutex::new(SettingsState {
+    path: None,
+    settings: AppSettings {
+        fnm_env: FnmEnvOverrides {
+            fnm_dir: None,
+            node_dist_mirror: None,
+            version_file_strategy: None,
+            resolve_engines: None,
+            arch: None,
+            loglevel: None,
+        },
+        prune_policy: PrunePolicy::DEFAULT,
+        version_sources: None,
+    },
+});
+
+/// 在应用启动时加载设置文件，文件不存在时使用默认设置；
+/// 无法解析时先将原文件备份为 settings.json.bak 再使用默认设置，备份失败则返回错误
+pub fn init_app_settings(config_dir: PathBuf) -> Result<(), FnmGuiError> {
+    let path = config_dir.join(SETTINGS_FILE);
+    let settings = load_settings(&path, &config_dir.join(SETTINGS_BACKUP_FILE))?;
+
+    let mut state = SETTINGS.lock().unwrap();
+    state.path = Some(path);
+    state.settings = settings;
+    Ok(())
+}
+
+fn load_settings(path: &Path, backup: &Path) -> Result<AppSettings, FnmGuiError> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Ok(AppSettings::default());
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(settings) => Ok(settings),
+        Err(_) => {
+            std::fs::rename(path, backup).map_err(|e| FnmGuiError::io("备份无法解析的设置文件失败", e))?;
+            Ok(AppSettings::default())
+        }
+    }
+}
+
+/// 获取当前的 fnm 配置覆盖值
+pub fn fnm_env_overrides() -> FnmEnvOverrides {
+    SETTINGS.lock().unwrap().settings.fnm_env.clone()
+}
+
+/// 保存 fnm 配置覆盖值并写入设置文件
+pub fn save_fnm_env_overrides(overrides: FnmEnvOverrides) -> Result<FnmEnvOverrides, FnmGuiError> {
+    let overrides = overrides.normalized();
+    overrides.validate()?;
+    update_settings(|settings| settings.fnm_env = overrides.clone())?;
+    Ok(overrides)
+}
+
+/// 获取当前的清理策略
+pub fn prune_policy() -> PrunePolicy {
+    SETTINGS.lock().unwrap().settings.prune_policy.clone()
+}
+
+/// 保存清理策略并写入设置文件
+pub fn save_prune_policy(policy: PrunePolicy) -> Result<PrunePolicy, FnmGuiError> {
+    update_settings(|settings| settings.prune_policy = policy.clone())?;
+    Ok(policy)
+}
+
+/// 获取项目版本来源的优先级
+pub fn version_sources() -> Vec<VersionSource> {
+    SETTINGS
+        .lock()
+        .unwrap()
+        .settings
+        .version_sources
+        .clone()
+        .unwrap_or_else(|| VersionSource::DEFAULT_ORDER.to_vec())
+}
+
+/// 保存项目版本来源的优先级并写入设置文件，重复的来源只保留第一个，None 表示恢复默认顺序
+pub fn save_version_sources(
+    sources: Option<Vec<VersionSource>>,
+) -> Result<Vec<VersionSource>, FnmGuiError> {
+    let sources = sources.map(|sources| {
+        let mut unique = Vec::new();
+        for source in sources {
+            if !unique.contains(&source) {
+                unique.push(source);
+            }
+        }
+        unique
+    });
+    update_settings(|settings| settings.version_sources = sources.clone())?;
+    Ok(version_sources())
+}
+
+/// 修改设置并写入设置文件，写入失败时保持原设置不变
+fn update_settings(change: impl FnOnce(&mut AppSettings)) -> Result<(), FnmGuiError> {
+    let mut state = SETTINGS.lock().unwrap();
+
+    let mut settings = state.settings.clone();
+    change(&mut settings);
+
+    if let Some(ref path) = state.path {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| FnmGuiError::io("创建配置目录失败", e))?;
+        }
+        let content = serde_json::to_string_pretty(&settings).map_err(|e| FnmGuiError::parse("设置", e))?;
+        std::fs::write(path, content).map_err(|e| FnmGuiError::io("保存设置失败", e))?;
+    }
+
+    state.settings = settings;
+    Ok(())
+}
+
+/// 将 fnm 配置覆盖值注入到 fnm 进程的环境变量中
+pub fn apply_fnm_env_overrides(cmd: &mut Command) {
+    for (key, value) in fnm_env_overrides().to_env_vars() {
+        cmd.env(key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unparsable_settings_are_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+        let backup = dir.path().join(SETTINGS_BACKUP_FILE);
+
+        assert!(load_settings(&path, &backup).unwrap().version_sources.is_none());
+        assert!(!backup.exists());
+
+        std::fs::write(&path, r#"{"fnmEnv":{"arch":"arm64"}}"#).unwrap();
+        assert_eq!(load_settings(&path, &backup).unwrap().fnm_env.arch.as_deref(), Some("arm64"));
+        assert!(!backup.exists());
+
+        std::fs::write(&path, r#"{"fnmEnv":"#).unwrap();
+        assert_eq!(load_settings(&path, &backup).unwrap().fnm_env, FnmEnvOverrides::default());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), r#"{"fnmEnv":"#);
+    }
+
+    #[test]
+    fn rejects_values_fnm_does_not_support() {
+        let valid = FnmEnvOverrides {
+            version_file_strategy: Some("recursive".into()),
+            arch: Some("arm64".into()),
+            loglevel: Some("quiet".into()),
+            ..Default::default()
+        };
+        assert!(valid.validate().is_ok());
+        assert!(FnmEnvOverrides::default().validate().is_ok());
+
+        for invalid in [
+            FnmEnvOverrides { version_file_strategy: Some("local; rm -rf ~".into()), ..valid.clone() },
+            FnmEnvOverrides { arch: Some("amd64".into()), ..valid.clone() },
+            FnmEnvOverrides { loglevel: Some("debug".into()), ..valid.clone() },
+        ] {
+            assert_eq!(invalid.validate().unwrap_err().code(), "invalidInput");
+        }
+    }
+}
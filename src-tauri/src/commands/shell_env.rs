@@ -0,0 +1,469 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// fnm env 支持输出的 shell 格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellDialect {
+    /// bash / zsh: export KEY="value"
+    Posix,
+    /// fish: set -gx KEY "value"
+    Fish,
+    /// PowerShell: $env:KEY = "value"
+    PowerShell,
+    /// cmd: SET KEY=value
+    Cmd,
+}
+
+/// 从 shell 脚本中解析出的环境变量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellEnv {
+    /// 除 PATH 以外的变量，后出现的值覆盖先出现的
+    pub vars: HashMap<String, String>,
+    /// 添加到 PATH 前面的目录（如 fnm_multishells 下的 bin 目录）
+    pub path_prepend: Vec<String>,
+}
+
+/// 解析 fnm env 输出或 shell 配置文件中的变量赋值，逐行自动识别格式，
+/// 无法识别的行（如 rehash、注释、条件语句）会被忽略
+pub fn parse_shell_env(script: &str) -> ShellEnv {
+    let mut env = ShellEnv::default();
+
+    for line in script.lines() {
+        let Some((dialect, key, value)) = parse_assignment(line) else {
+            continue;
+        };
+
+        if key.eq_ignore_ascii_case("PATH") {
+            env.path_prepend = path_prepend(dialect, &value);
+        } else {
+            env.vars.insert(key, value);
+        }
+    }
+
+    env
+}
+
+/// 解析单行赋值语句，返回 (格式, 变量名, 值)
+pub fn parse_assignment(line: &str) -> Option<(ShellDialect, String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("REM ") {
+        return None;
+    }
+
+    if let Some(rest) = line.strip_prefix("$env:") {
+        return parse_powershell(rest).map(|(k, v)| (ShellDialect::PowerShell, k, v));
+    }
+
+    if let Some(rest) = strip_prefix_ignore_case(line, "set ") {
+        let rest = rest.trim_start();
+        // cmd 的 set 紧跟 KEY=，fish 的 set 后为选项或以空格分隔的 KEY value
+        let first = rest.split_whitespace().next().unwrap_or("");
+        if !first.starts_with('-') && (first.contains('=') || rest.starts_with('"')) {
+            return parse_cmd(rest).map(|(k, v)| (ShellDialect::Cmd, k, v));
+        }
+        return parse_fish(rest).map(|(k, v)| (ShellDialect::Fish, k, v));
+    }
+
+    parse_posix(line).map(|(k, v)| (ShellDialect::Posix, k, v))
+}
+
+/// export KEY="value" 或 KEY=value
+fn parse_posix(line: &str) -> Option<(String, String)> {
+    let words = split_words(line);
+    let mut words = words.into_iter();
+    let mut word = words.next()?;
+    if word == "export" {
+        word = words.next()?;
+    }
+
+    let (key, value) = word.split_once('=')?;
+    is_identifier(key).then(|| (key.to_string(), value.to_string()))
+}
+
+/// set -gx KEY "value" [更多值]
+fn parse_fish(rest: &str) -> Option<(String, String)> {
+    let mut words = split_words(rest)
+        .into_iter()
+        .skip_while(|w| w.starts_with('-'));
+    let key = words.next()?;
+    if !is_identifier(&key) {
+        return None;
+    }
+
+    let values: Vec<String> = words.collect();
+    let separator = if key.eq_ignore_ascii_case("PATH") { ":" } else { " " };
+    Some((key, values.join(separator)))
+}
+
+/// KEY=value 或 "KEY=value"，cmd 不处理引号和转义，值保留到行尾
+fn parse_cmd(rest: &str) -> Option<(String, String)> {
+    let rest = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"').unwrap_or(quoted),
+        None => rest,
+    };
+
+    let (key, value) = rest.split_once('=')?;
+    let key = key.trim();
+    is_identifier(key).then(|| (key.to_string(), value.to_string()))
+}
+
+/// KEY = "value"（已去掉 $env: 前缀）
+fn parse_powershell(rest: &str) -> Option<(String, String)> {
+    let (key, value) = rest.split_once('=')?;
+    let key = key.trim();
+    if !is_identifier(key) {
+        return None;
+    }
+
+    let value = value.trim().trim_end_matches(';').trim_end();
+    let mut chars = value.chars().peekable();
+    let mut result = String::new();
+
+    match chars.next()? {
+        '"' => {
+            // 双引号字符串：反引号转义，"" 表示一个引号
+            while let Some(c) = chars.next() {
+                match c {
+                    '`' => match chars.next()? {
+                        'n' => result.push('\n'),
+                        't' => result.push('\t'),
+                        '0' => result.push('\0'),
+                        other => result.push(other),
+                    },
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        result.push('"');
+                    }
+                    '"' => break,
+                    other => result.push(other),
+                }
+            }
+        }
+        '\'' => {
+            // 单引号字符串：'' 表示一个引号
+            while let Some(c) = chars.next() {
+                match c {
+                    '\'' if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        result.push('\'');
+                    }
+                    '\'' => break,
+                    other => result.push(other),
+                }
+            }
+        }
+        first => {
+            result.push(first);
+            result.extend(chars.take_while(|c| !c.is_whitespace()));
+        }
+    }
+
+    Some((key.to_string(), result))
+}
+
+/// 按 POSIX / fish 规则拆分单词：处理单双引号和反斜杠转义，遇到未加引号的 ; 或 # 结束
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            ';' => break,
+            '#' if !in_word => break,
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(next @ ('"' | '\\' | '$' | '`')) => current.push(next),
+                            Some(next) => {
+                                current.push('\\');
+                                current.push(next);
+                            }
+                            None => current.push('\\'),
+                        },
+                        other => current.push(other),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            other => {
+                in_word = true;
+                current.push(other);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    words
+}
+
+/// 提取 PATH 赋值中添加到原 PATH 前面的目录
+/// bash/fish 会引用原 PATH（$PATH），PowerShell/cmd 输出的是展开后的完整 PATH，此时取第一项
+fn path_prepend(dialect: ShellDialect, value: &str) -> Vec<String> {
+    let separator = match dialect {
+        ShellDialect::PowerShell | ShellDialect::Cmd => ';',
+        ShellDialect::Posix | ShellDialect::Fish => ':',
+    };
+
+    let is_path_reference = |entry: &str| {
+        matches!(
+            entry,
+            "$PATH" | "${PATH}" | "$env:PATH" | "$env:Path" | "%PATH%" | "%Path%"
+        )
+    };
+
+    let entries: Vec<&str> = value.split(separator).filter(|e| !e.is_empty()).collect();
+
+    if entries.iter().any(|e| is_path_reference(e)) {
+        entries
+            .into_iter()
+            .take_while(|e| !is_path_reference(e))
+            .map(|e| e.to_string())
+            .collect()
+    } else {
+        entries.into_iter().take(1).map(|e| e.to_string()).collect()
+    }
+}
+
+/// 是否为合法的环境变量名：[A-Za-z_][A-Za-z0-9_]*
+pub fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn strip_prefix_ignore_case<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = line.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &line[prefix.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (shell, 脚本, 期望的变量, 期望的 path_prepend)
+    type Sample = (&'static str, &'static str, &'static [(&'static str, &'static str)], &'static [&'static str]);
+
+    /// 各 shell 下 `fnm env --use-on-cd` 的实际输出（去掉 use-on-cd 钩子部分）
+    const SAMPLES: &[Sample] = &[
+        (
+            "bash",
+            r#"export PATH="/run/user/1000/fnm_multishells/4242_1700000000000/bin":"$PATH"
+export FNM_MULTISHELL_PATH="/run/user/1000/fnm_multishells/4242_1700000000000"
+export FNM_VERSION_FILE_STRATEGY="local"
+export FNM_DIR="/home/dev/.local/share/fnm"
+export FNM_LOGLEVEL="info"
+export FNM_NODE_DIST_MIRROR="https://nodejs.org/dist"
+export FNM_COREPACK_ENABLED="false"
+export FNM_RESOLVE_ENGINES="true"
+export FNM_ARCH="x64"
+__fnm_use_if_file_found() {
+    if [[ -f .node-version || -f .nvmrc || -f package.json ]]; then
+        fnm use --silent-if-unchanged
+    fi
+}
+"#,
+            &[
+                ("FNM_MULTISHELL_PATH", "/run/user/1000/fnm_multishells/4242_1700000000000"),
+                ("FNM_VERSION_FILE_STRATEGY", "local"),
+                ("FNM_DIR", "/home/dev/.local/share/fnm"),
+                ("FNM_LOGLEVEL", "info"),
+                ("FNM_NODE_DIST_MIRROR", "https://nodejs.org/dist"),
+                ("FNM_COREPACK_ENABLED", "false"),
+                ("FNM_RESOLVE_ENGINES", "true"),
+                ("FNM_ARCH", "x64"),
+            ],
+            &["/run/user/1000/fnm_multishells/4242_1700000000000/bin"],
+        ),
+        (
+            "zsh",
+            r#"export PATH="/Users/dev/Library/Caches/fnm_multishells/501_1700000000000/bin":"$PATH"
+export FNM_MULTISHELL_PATH="/Users/dev/Library/Caches/fnm_multishells/501_1700000000000"
+export FNM_VERSION_FILE_STRATEGY="recursive"
+export FNM_DIR="/Users/dev/Library/Application Support/fnm"
+export FNM_ARCH="arm64"
+rehash
+"#,
+            &[
+                ("FNM_MULTISHELL_PATH", "/Users/dev/Library/Caches/fnm_multishells/501_1700000000000"),
+                ("FNM_VERSION_FILE_STRATEGY", "recursive"),
+                ("FNM_DIR", "/Users/dev/Library/Application Support/fnm"),
+                ("FNM_ARCH", "arm64"),
+            ],
+            &["/Users/dev/Library/Caches/fnm_multishells/501_1700000000000/bin"],
+        ),
+        (
+            "fish",
+            r#"set -gx PATH "/run/user/1000/fnm_multishells/4242_1700000000000/bin" $PATH;
+set -gx FNM_MULTISHELL_PATH "/run/user/1000/fnm_multishells/4242_1700000000000";
+set -gx FNM_VERSION_FILE_STRATEGY "local";
+set -gx FNM_DIR "/home/dev/.local/share/fnm";
+set -gx FNM_NODE_DIST_MIRROR "https://npmmirror.com/mirrors/node";
+"#,
+            &[
+                ("FNM_MULTISHELL_PATH", "/run/user/1000/fnm_multishells/4242_1700000000000"),
+                ("FNM_VERSION_FILE_STRATEGY", "local"),
+                ("FNM_DIR", "/home/dev/.local/share/fnm"),
+                ("FNM_NODE_DIST_MIRROR", "https://npmmirror.com/mirrors/node"),
+            ],
+            &["/run/user/1000/fnm_multishells/4242_1700000000000/bin"],
+        ),
+        (
+            "powershell",
+            r#"$env:PATH = "C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000;C:\Windows\system32;C:\Windows"
+$env:FNM_MULTISHELL_PATH = "C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000"
+$env:FNM_VERSION_FILE_STRATEGY = "local"
+$env:FNM_DIR = "C:\Users\dev\AppData\Roaming\fnm"
+$env:FNM_COREPACK_ENABLED = "true"
+"#,
+            &[
+                ("FNM_MULTISHELL_PATH", r"C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000"),
+                ("FNM_VERSION_FILE_STRATEGY", "local"),
+                ("FNM_DIR", r"C:\Users\dev\AppData\Roaming\fnm"),
+                ("FNM_COREPACK_ENABLED", "true"),
+            ],
+            &[r"C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000"],
+        ),
+        (
+            "cmd",
+            r#"SET PATH=C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000;C:\Windows\system32;C:\Windows
+SET FNM_MULTISHELL_PATH=C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000
+SET FNM_VERSION_FILE_STRATEGY=local
+SET FNM_DIR=C:\Users\dev\AppData\Roaming\fnm
+SET FNM_LOGLEVEL=info
+"#,
+            &[
+                ("FNM_MULTISHELL_PATH", r"C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000"),
+                ("FNM_VERSION_FILE_STRATEGY", "local"),
+                ("FNM_DIR", r"C:\Users\dev\AppData\Roaming\fnm"),
+                ("FNM_LOGLEVEL", "info"),
+            ],
+            &[r"C:\Users\dev\AppData\Local\fnm_multishells\9001_1700000000000"],
+        ),
+    ];
+
+    #[test]
+    fn parses_fnm_env_for_every_shell() {
+        for (shell, script, vars, path) in SAMPLES {
+            let env = parse_shell_env(script);
+            let expected: HashMap<String, String> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            assert_eq!(env.vars, expected, "{shell}");
+            assert_eq!(env.path_prepend, *path, "{shell}");
+        }
+    }
+
+    #[test]
+    fn parses_quoting_and_escaping() {
+        let cases: &[(&str, ShellDialect, &str, &str)] = &[
+            // POSIX：单引号原样保留，双引号内处理 \" \\ \$，未加引号时反斜杠转义下一个字符
+            (r#"export A="C:\\Users\\dev""#, ShellDialect::Posix, "A", r"C:\Users\dev"),
+            (r#"export A="say \"hi\" \$HOME""#, ShellDialect::Posix, "A", r#"say "hi" $HOME"#),
+            (r#"export A='it''s \n'"#, ShellDialect::Posix, "A", r"its \n"),
+            (r"A=with\ space", ShellDialect::Posix, "A", "with space"),
+            (r#"export A="x" # comment"#, ShellDialect::Posix, "A", "x"),
+            (r#"export A="x"; rehash"#, ShellDialect::Posix, "A", "x"),
+            (r#"export A="a\qb""#, ShellDialect::Posix, "A", r"a\qb"),
+            // fish：多个值以空格连接
+            (r#"set -gx A 'one' "two three";"#, ShellDialect::Fish, "A", "one two three"),
+            (r#"set -x -g A "x\"y""#, ShellDialect::Fish, "A", r#"x"y"#),
+            // PowerShell：反引号转义、"" 与 '' 表示引号
+            (r#"$env:A = "tab`there""#, ShellDialect::PowerShell, "A", "tab\there"),
+            (r#"$env:A = "say ""hi""";"#, ShellDialect::PowerShell, "A", r#"say "hi""#),
+            (r#"$env:A = 'it''s'"#, ShellDialect::PowerShell, "A", "it's"),
+            (r#"$env:A = "`$HOME""#, ShellDialect::PowerShell, "A", "$HOME"),
+            (r"$env:A = bare value", ShellDialect::PowerShell, "A", "bare"),
+            // cmd：不处理转义，整条赋值可用引号包裹
+            (r#"set "A=x & y""#, ShellDialect::Cmd, "A", "x & y"),
+            (r#"SET A="quoted""#, ShellDialect::Cmd, "A", r#""quoted""#),
+            (r"set A=%USERPROFILE%\fnm", ShellDialect::Cmd, "A", r"%USERPROFILE%\fnm"),
+        ];
+
+        for (line, dialect, key, value) in cases {
+            assert_eq!(
+                parse_assignment(line),
+                Some((*dialect, key.to_string(), value.to_string())),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_non_assignments() {
+        for line in [
+            "",
+            "   ",
+            "# export A=1",
+            "REM SET A=1",
+            "rehash",
+            "fnm use --silent-if-unchanged",
+            "export 1A=x",
+            "export A-B=x",
+            "set -gx",
+            "$env:A-B = 'x'",
+        ] {
+            assert_eq!(parse_assignment(line), None, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn extracts_path_prepend() {
+        let cases: &[(ShellDialect, &str, &[&str])] = &[
+            (ShellDialect::Posix, "/a/bin:/b/bin:$PATH", &["/a/bin", "/b/bin"]),
+            (ShellDialect::Posix, "/a/bin:${PATH}:/late/bin", &["/a/bin"]),
+            (ShellDialect::Posix, "/a/bin:/usr/bin", &["/a/bin"]),
+            (ShellDialect::Fish, "/a/bin:$PATH", &["/a/bin"]),
+            (ShellDialect::PowerShell, r"C:\fnm\bin;$env:Path", &[r"C:\fnm\bin"]),
+            (ShellDialect::PowerShell, r"C:\fnm\bin;C:\Windows", &[r"C:\fnm\bin"]),
+            (ShellDialect::Cmd, r"C:\fnm\bin;%PATH%", &[r"C:\fnm\bin"]),
+            (ShellDialect::Cmd, r";C:\fnm\bin;;C:\Windows", &[r"C:\fnm\bin"]),
+            (ShellDialect::Posix, "$PATH", &[]),
+            (ShellDialect::Posix, "", &[]),
+        ];
+
+        for (dialect, value, expected) in cases {
+            assert_eq!(path_prepend(*dialect, value), *expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn later_assignments_win() {
+        let env = parse_shell_env(
+            "export FNM_DIR=/old\nset -gx FNM_DIR /new;\nexport PATH=/x/bin:$PATH\n$env:PATH = \"C:\\y;C:\\z\"\n",
+        );
+        assert_eq!(env.vars["FNM_DIR"], "/new");
+        assert_eq!(env.path_prepend, [r"C:\y"]);
+    }
+}
@@ -0,0 +1,501 @@
+use super::settings::VERSION_FILE_STRATEGIES;
+use super::shell_env::is_identifier;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::command;
+
+const BLOCK_START: &str = "# >>> fnm-gui managed block >>>";
+const BLOCK_END: &str = "# <<< fnm-gui managed block <<<";
+const BLOCK_NOTICE: &str = "# 此区块由 fnm-gui 管理，修改会在下次保存时被覆盖";
+
+/// 支持集成的 shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    const ALL: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+    fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// 该 shell 的配置文件路径
+    /// fish 使用 conf.d 下由 fnm-gui 独占的文件，避免改动用户的 config.fish 或其他工具写入的 fnm.fish
+    fn profile_path(self) -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+
+        Some(match self {
+            Shell::Bash => home.join(".bashrc"),
+            Shell::Zsh => std::env::var("ZDOTDIR")
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or(home)
+                .join(".zshrc"),
+            Shell::Fish => home.join(".config/fish/conf.d/fnm-gui.fish"),
+        })
+    }
+
+    /// 该 shell 是否安装在系统中
+    fn is_installed(self) -> bool {
+        ["/bin", "/usr/bin", "/usr/local/bin", "/opt/homebrew/bin"]
+            .iter()
+            .any(|dir| Path::new(dir).join(self.name()).is_file())
+    }
+}
+
+/// 写入配置文件的 fnm 集成选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellIntegrationOptions {
+    /// 进入目录时自动切换版本（--use-on-cd）
+    pub use_on_cd: bool,
+    pub version_file_strategy: Option<String>,
+    pub corepack_enabled: bool,
+    pub resolve_engines: Option<bool>,
+    /// 额外导出的环境变量，如 FNM_NODE_DIST_MIRROR
+    pub env_vars: BTreeMap<String, String>,
+}
+
+/// 某个 shell 的集成状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellIntegrationStatus {
+    pub shell: Shell,
+    pub profile_path: String,
+    /// shell 是否已安装在系统中
+    pub installed: bool,
+    /// 是否为用户的登录 shell（$SHELL）
+    pub is_login_shell: bool,
+    pub profile_exists: bool,
+    /// 是否存在 fnm-gui 管理的区块
+    pub managed: bool,
+    /// 管理区块之外是否已有用户手动添加的 fnm env
+    pub manual_setup: bool,
+    /// 当前管理区块的内容
+    pub managed_block: Option<String>,
+}
+
+/// 修改配置文件的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellProfileChange {
+    pub shell: Shell,
+    pub profile_path: String,
+    /// 修改前的备份文件，原文件不存在时为 None
+    pub backup_path: Option<String>,
+}
+
+/// 检测各 shell 的 fnm 集成状态
+#[command]
+pub fn get_shell_integrations() -> Result<Vec<ShellIntegrationStatus>, FnmGuiError> {
+    let login_shell = std::env::var("SHELL").unwrap_or_default();
+
+    Shell::ALL
+        .iter()
+        .map(|&shell| {
+            let path = shell
+                .profile_path()
+                .ok_or(FnmGuiError::HomeDirUnavailable)?;
+            let content = std::fs::read_to_string(&path).ok();
+            let block = content.as_deref().and_then(find_managed_block);
+            let outside = match (&content, block) {
+                (Some(content), Some((start, end))) => {
+                    format!("{}{}", &content[..start], &content[end..])
+                }
+                (Some(content), None) => content.clone(),
+                _ => String::new(),
+            };
+
+            Ok(ShellIntegrationStatus {
+                shell,
+                profile_path: path.to_string_lossy().to_string(),
+                installed: shell.is_installed(),
+                is_login_shell: Path::new(&login_shell)
+                    .file_name()
+                    .is_some_and(|name| name == shell.name()),
+                profile_exists: content.is_some(),
+                managed: block.is_some(),
+                manual_setup: outside
+                    .lines()
+                    .any(|line| !line.trim_start().starts_with('#') && line.contains("fnm env")),
+                managed_block: content
+                    .as_deref()
+                    .zip(block)
+                    .map(|(content, (start, end))| content[start..end].to_string()),
+            })
+        })
+        .collect()
+}
+
+/// 在配置文件中写入或更新 fnm-gui 管理区块，修改前会备份原文件
+#[command]
+pub fn install_shell_integration(
+    shell: Shell,
+    options: ShellIntegrationOptions,
+) -> Result<ShellProfileChange, FnmGuiError> {
+    let block = render_block(shell, &options)?;
+    let path = shell
+        .profile_path()
+        .ok_or(FnmGuiError::HomeDirUnavailable)?;
+
+    update_profile(shell, &path, |content| Some(insert_block(content, &block)))
+}
+
+/// 从配置文件中移除 fnm-gui 管理区块
+#[command]
+pub fn remove_shell_integration(shell: Shell) -> Result<ShellProfileChange, FnmGuiError> {
+    let path = shell
+        .profile_path()
+        .ok_or(FnmGuiError::HomeDirUnavailable)?;
+
+    update_profile(shell, &path, remove_block)
+}
+
+/// 替换已有的管理区块，没有时追加到文件末尾，与前面的内容之间空一行
+fn insert_block(content: &str, block: &str) -> String {
+    if let Some((start, end)) = find_managed_block(content) {
+        return format!("{}{}{}", &content[..start], block, &content[end..]);
+    }
+
+    let mut updated = content.to_string();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated.push_str(block);
+    updated
+}
+
+/// 移除管理区块，没有区块时返回 None
+fn remove_block(content: &str) -> Option<String> {
+    find_managed_block(content).map(|(start, end)| {
+        // 同时去掉写入区块时在前面添加的空行
+        let before = &content[..start];
+        let before = before
+            .strip_suffix("\n\n")
+            .map_or(before, |b| &before[..b.len() + 1]);
+        format!("{}{}", before, &content[end..])
+    })
+}
+
+/// 备份并改写配置文件，update 返回 None 表示无需修改
+fn update_profile(
+    shell: Shell,
+    path: &Path,
+    update: impl FnOnce(&str) -> Option<String>,
+) -> Result<ShellProfileChange, FnmGuiError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(FnmGuiError::io(format!("读取 {} 失败", path.display()), e)),
+    };
+
+    let mut change = ShellProfileChange {
+        shell,
+        profile_path: path.to_string_lossy().to_string(),
+        backup_path: None,
+    };
+
+    let Some(updated) = update(content.as_deref().unwrap_or("")) else {
+        return Ok(change);
+    };
+
+    if content.is_some() {
+        let backup = backup_profile(path)?;
+        change.backup_path = Some(backup.to_string_lossy().to_string());
+    } else if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| FnmGuiError::io(format!("创建目录 {} 失败", parent.display()), e))?;
+    }
+
+    std::fs::write(path, updated)
+        .map_err(|e| FnmGuiError::io(format!("写入 {} 失败", path.display()), e))?;
+    Ok(change)
+}
+
+/// 复制为 <文件名>.fnm-gui-<时间戳>.bak
+fn backup_profile(path: &Path) -> Result<PathBuf, FnmGuiError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup = path.with_file_name(format!("{}.fnm-gui-{}.bak", file_name, timestamp));
+
+    std::fs::copy(path, &backup)
+        .map_err(|e| FnmGuiError::io(format!("备份 {} 失败", path.display()), e))?;
+    Ok(backup)
+}
+
+/// 查找管理区块的字节范围，包含结束标记所在行的换行符
+fn find_managed_block(content: &str) -> Option<(usize, usize)> {
+    let start = content.find(BLOCK_START)?;
+    let end_marker = start + content[start..].find(BLOCK_END)?;
+    let mut end = end_marker + BLOCK_END.len();
+    if content[end..].starts_with('\n') {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+/// 生成管理区块内容，拒绝会被 shell 当作命令执行的版本文件策略和变量名
+fn render_block(shell: Shell, options: &ShellIntegrationOptions) -> Result<String, FnmGuiError> {
+    let mut flags = Vec::new();
+    if options.use_on_cd {
+        flags.push("--use-on-cd".to_string());
+    }
+    if let Some(ref strategy) = options.version_file_strategy {
+        if !VERSION_FILE_STRATEGIES.contains(&strategy.as_str()) {
+            return Err(FnmGuiError::InvalidInput(format!(
+                "不支持的版本文件策略: {}，可选值: {}",
+                strategy,
+                VERSION_FILE_STRATEGIES.join(", ")
+            )));
+        }
+        flags.push(format!("--version-file-strategy={}", strategy));
+    }
+    if options.corepack_enabled {
+        flags.push("--corepack-enabled".to_string());
+    }
+    if options.resolve_engines == Some(true) {
+        flags.push("--resolve-engines".to_string());
+    }
+    flags.push(format!("--shell={}", shell.name()));
+    let flags = flags.join(" ");
+
+    let mut lines = vec![BLOCK_START.to_string(), BLOCK_NOTICE.to_string()];
+
+    for (key, value) in &options.env_vars {
+        if !is_identifier(key) {
+            return Err(FnmGuiError::InvalidInput(format!(
+                "无效的环境变量名: {}",
+                key
+            )));
+        }
+        lines.push(match shell {
+            Shell::Fish => format!("set -gx {} {}", key, quote(shell, value)),
+            Shell::Bash | Shell::Zsh => format!("export {}={}", key, quote(shell, value)),
+        });
+    }
+
+    lines.push(match shell {
+        Shell::Fish => format!("fnm env {} | source", flags),
+        Shell::Bash | Shell::Zsh => format!("eval \"$(fnm env {})\"", flags),
+    });
+    lines.push(BLOCK_END.to_string());
+
+    let mut block = lines.join("\n");
+    block.push('\n');
+    Ok(block)
+}
+
+/// 用双引号包裹并转义特殊字符，fish 的双引号中反引号没有特殊含义
+fn quote(shell: Shell, value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        let special = match shell {
+            Shell::Fish => matches!(c, '"' | '\\' | '$'),
+            Shell::Bash | Shell::Zsh => matches!(c, '"' | '\\' | '$' | '`'),
+        };
+        if special {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::shell_env::parse_shell_env;
+    use super::*;
+
+    fn options() -> ShellIntegrationOptions {
+        ShellIntegrationOptions {
+            use_on_cd: true,
+            version_file_strategy: Some("recursive".into()),
+            corepack_enabled: true,
+            resolve_engines: Some(true),
+            env_vars: BTreeMap::from([
+                (
+                    "FNM_NODE_DIST_MIRROR".to_string(),
+                    "https://npmmirror.com/mirrors/node".to_string(),
+                ),
+                ("QUOTED".to_string(), r#"a "b" $HOME `id` \ c"#.to_string()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn renders_block_for_each_shell() {
+        let posix = |shell: &str| {
+            format!(
+                "{BLOCK_START}\n{BLOCK_NOTICE}\n\
+                 export FNM_NODE_DIST_MIRROR=\"https://npmmirror.com/mirrors/node\"\n\
+                 export QUOTED=\"a \\\"b\\\" \\$HOME \\`id\\` \\\\ c\"\n\
+                 eval \"$(fnm env --use-on-cd --version-file-strategy=recursive --corepack-enabled --resolve-engines --shell={shell})\"\n\
+                 {BLOCK_END}\n"
+            )
+        };
+        let fish = format!(
+            "{BLOCK_START}\n{BLOCK_NOTICE}\n\
+             set -gx FNM_NODE_DIST_MIRROR \"https://npmmirror.com/mirrors/node\"\n\
+             set -gx QUOTED \"a \\\"b\\\" \\$HOME `id` \\\\ c\"\n\
+             fnm env --use-on-cd --version-file-strategy=recursive --corepack-enabled --resolve-engines --shell=fish | source\n\
+             {BLOCK_END}\n"
+        );
+
+        assert_eq!(
+            render_block(Shell::Bash, &options()).unwrap(),
+            posix("bash")
+        );
+        assert_eq!(render_block(Shell::Zsh, &options()).unwrap(), posix("zsh"));
+        assert_eq!(render_block(Shell::Fish, &options()).unwrap(), fish);
+    }
+
+    #[test]
+    fn rendered_values_survive_shell_quoting() {
+        for shell in Shell::ALL {
+            let block = render_block(shell, &options()).unwrap();
+            let env = parse_shell_env(&block);
+            assert_eq!(env.vars.len(), 2, "{}", shell.name());
+            for (key, value) in &options().env_vars {
+                assert_eq!(&env.vars[key], value, "{}", shell.name());
+            }
+        }
+    }
+
+    #[test]
+    fn renders_minimal_block() {
+        let block = render_block(Shell::Bash, &ShellIntegrationOptions::default()).unwrap();
+        assert_eq!(
+            block,
+            format!(
+                "{BLOCK_START}\n{BLOCK_NOTICE}\neval \"$(fnm env --shell=bash)\"\n{BLOCK_END}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_injected_strategy_and_keys() {
+        for strategy in ["local; rm -rf ~", "$(id)", "Local", ""] {
+            let options = ShellIntegrationOptions {
+                version_file_strategy: Some(strategy.into()),
+                ..Default::default()
+            };
+            for shell in Shell::ALL {
+                let err = render_block(shell, &options).unwrap_err();
+                assert_eq!(err.code(), "invalidInput", "{strategy:?}");
+            }
+        }
+
+        for key in ["A=1; id #", "1A", "A-B", "$(id)", "", "A B"] {
+            let options = ShellIntegrationOptions {
+                env_vars: BTreeMap::from([(key.to_string(), "x".to_string())]),
+                ..Default::default()
+            };
+            for shell in Shell::ALL {
+                let err = render_block(shell, &options).unwrap_err();
+                assert_eq!(err.code(), "invalidInput", "{key:?}");
+            }
+        }
+    }
+
+    fn install(path: &Path, options: &ShellIntegrationOptions) -> ShellProfileChange {
+        let block = render_block(Shell::Bash, options).unwrap();
+        update_profile(Shell::Bash, path, |content| {
+            Some(insert_block(content, &block))
+        })
+        .unwrap()
+    }
+
+    fn read(path: impl AsRef<Path>) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn installs_into_a_new_profile_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zdotdir/.bashrc");
+
+        let change = install(&path, &ShellIntegrationOptions::default());
+
+        assert_eq!(change.backup_path, None);
+        assert_eq!(
+            read(&path),
+            render_block(Shell::Bash, &Default::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn installs_after_content_without_trailing_newline_and_backs_it_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".bashrc");
+        std::fs::write(&path, "alias ll='ls -l'").unwrap();
+
+        let change = install(&path, &ShellIntegrationOptions::default());
+
+        let block = render_block(Shell::Bash, &Default::default()).unwrap();
+        assert_eq!(read(&path), format!("alias ll='ls -l'\n\n{block}"));
+        let backup = PathBuf::from(change.backup_path.unwrap());
+        assert_eq!(backup.parent(), Some(dir.path()));
+        let backup_name = backup.file_name().unwrap().to_string_lossy().to_string();
+        assert!(backup_name.starts_with(".bashrc.fnm-gui-"));
+        assert!(backup_name.ends_with(".bak"));
+        assert_eq!(read(&backup), "alias ll='ls -l'");
+    }
+
+    #[test]
+    fn reinstall_replaces_the_block_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".bashrc");
+        let old = render_block(Shell::Bash, &Default::default()).unwrap();
+        std::fs::write(&path, format!("export A=1\n\n{old}export B=2\n")).unwrap();
+
+        install(&path, &options());
+
+        let new = render_block(Shell::Bash, &options()).unwrap();
+        assert_eq!(read(&path), format!("export A=1\n\n{new}export B=2\n"));
+        assert_eq!(
+            find_managed_block(&read(&path)).map(|(s, e)| e - s),
+            Some(new.len())
+        );
+    }
+
+    #[test]
+    fn remove_restores_the_original_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".bashrc");
+        std::fs::write(&path, "alias ll='ls -l'\n").unwrap();
+
+        install(&path, &options());
+        let change = update_profile(Shell::Bash, &path, remove_block).unwrap();
+
+        assert_eq!(read(&path), "alias ll='ls -l'\n");
+        assert!(change.backup_path.is_some());
+
+        // 没有管理区块时不改写也不备份
+        let change = update_profile(Shell::Bash, &path, remove_block).unwrap();
+        assert_eq!(change.backup_path, None);
+        assert_eq!(read(&path), "alias ll='ls -l'\n");
+    }
+}
@@ -0,0 +1,461 @@
+use super::env::read_fnm_env;
+use super::fnm_layout::{compare_versions, release_line, FnmDir};
+use super::global_packages::{global_bin_dir, global_packages, GlobalPackage};
+use super::jobs::{observer_for, run_job, JobKind, JobManager};
+use super::operation::{lock_current_fnm_dir, Operation, OperationKind};
+use super::projects::ProjectRegistry;
+use super::remote::{RemoteIndexClient, RemoteRelease};
+use super::runner::{run_blocking, CommandOutput, FnmRunner, ProcessRunner};
+use super::version::uninstall_version;
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tauri::{command, AppHandle, Emitter, State};
+
+/// 升级进度事件名
+pub const UPGRADE_PROGRESS_EVENT: &str = "upgrade-progress";
+
+/// 升级流程的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpgradeStep {
+    /// 查找最新的补丁版本
+    Resolve,
+    Install,
+    /// 在新版本中重新安装全局包
+    Globals,
+    /// 将指向旧版本的别名改为指向新版本
+    Aliases,
+    /// 卸载旧版本
+    Uninstall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// 升级进度事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeProgress {
+    pub line: String,
+    pub step: UpgradeStep,
+    pub status: StepStatus,
+    pub message: Option<String>,
+}
+
+/// 迁移失败的全局包
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageFailure {
+    pub name: String,
+    pub error: String,
+}
+
+/// 升级结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeReport {
+    /// 发布线，如 "20"
+    pub line: String,
+    pub from: String,
+    pub to: String,
+    /// 是否已经是最新版本
+    pub up_to_date: bool,
+    pub migrated_packages: Vec<String>,
+    pub failed_packages: Vec<PackageFailure>,
+    pub moved_aliases: Vec<String>,
+    pub uninstalled: bool,
+    /// 卸载旧版本失败或需要确认时的原因
+    pub uninstall_error: Option<String>,
+}
+
+/// 将某个主版本升级到最新的补丁版本：安装新版本、迁移全局包和别名，可选卸载旧版本
+/// 每个步骤通过 upgrade-progress 事件推送进度
+#[command]
+pub async fn upgrade_version(
+    app: AppHandle,
+    jobs: State<'_, JobManager>,
+    projects: State<'_, ProjectRegistry>,
+    major: String,
+    uninstall_old: Option<bool>,
+) -> Result<UpgradeReport, FnmGuiError> {
+    let line = release_line(major.trim());
+    let emit = |step, status, message: Option<String>| {
+        let _ = app.emit(
+            UPGRADE_PROGRESS_EVENT,
+            UpgradeProgress {
+                line: line.clone(),
+                step,
+                status,
+                message,
+            },
+        );
+    };
+
+    // 查找本地最新和远程最新的版本
+    emit(UpgradeStep::Resolve, StepStatus::Running, None);
+    let dir = run_blocking(FnmDir::current).await?;
+    let from = newest_installed(&dir, &line)
+        .ok_or_else(|| FnmGuiError::InvalidInput(format!("没有已安装的 Node {} 版本", line)))?;
+    let env = run_blocking(|| read_fnm_env(&ProcessRunner)).await?;
+    let releases = RemoteIndexClient::new(&env.node_dist_mirror)
+        .fetch_releases()
+        .await
+        .inspect_err(|e| {
+            emit(
+                UpgradeStep::Resolve,
+                StepStatus::Failed,
+                Some(e.to_string()),
+            )
+        })?;
+    let to = latest_release(&releases, &line)
+        .map(|release| release.version.clone())
+        .unwrap_or_else(|| from.clone());
+
+    let mut report = UpgradeReport {
+        line: line.clone(),
+        from: from.clone(),
+        to: to.clone(),
+        up_to_date: compare_versions(&to, &from).is_le(),
+        migrated_packages: Vec::new(),
+        failed_packages: Vec::new(),
+        moved_aliases: Vec::new(),
+        uninstalled: false,
+        uninstall_error: None,
+    };
+    if report.up_to_date {
+        emit(
+            UpgradeStep::Resolve,
+            StepStatus::Skipped,
+            Some(format!("{} 已是最新版本", from)),
+        );
+        return Ok(report);
+    }
+    emit(
+        UpgradeStep::Resolve,
+        StepStatus::Succeeded,
+        Some(format!("{} -> {}", from, to)),
+    );
+
+    // 安装新版本
+    if dir.is_installed(&to) {
+        emit(UpgradeStep::Install, StepStatus::Skipped, None);
+    } else {
+        emit(UpgradeStep::Install, StepStatus::Running, None);
+        let observer = observer_for(&app, JobKind::Install, &to).await;
+        run_job(
+            &ProcessRunner,
+            jobs.inner(),
+            JobKind::Install,
+            &to,
+            observer.as_ref(),
+        )
+        .await
+        .inspect_err(|e| {
+            emit(
+                UpgradeStep::Install,
+                StepStatus::Failed,
+                Some(e.to_string()),
+            )
+        })?;
+        emit(UpgradeStep::Install, StepStatus::Succeeded, None);
+    }
+
+    // 迁移全局包，单个包失败不中断升级；npm 会写入新版本的目录，期间持有 fnm 目录的操作锁
+    emit(UpgradeStep::Globals, StepStatus::Running, None);
+    let (migrated, failed) = {
+        let _guard = lock_current_fnm_dir(Operation::new(OperationKind::Install, &to))
+            .await
+            .inspect_err(|e| {
+                emit(
+                    UpgradeStep::Globals,
+                    StepStatus::Failed,
+                    Some(e.to_string()),
+                )
+            })?;
+        let (old, new) = (dir.installation_dir(&from), dir.installation_dir(&to));
+        run_blocking(move || Ok(replay_globals(&old, &new)))
+            .await
+            .inspect_err(|e| {
+                emit(
+                    UpgradeStep::Globals,
+                    StepStatus::Failed,
+                    Some(e.to_string()),
+                )
+            })?
+    };
+    report.migrated_packages = migrated;
+    report.failed_packages = failed;
+    emit(
+        UpgradeStep::Globals,
+        if report.failed_packages.is_empty() {
+            StepStatus::Succeeded
+        } else {
+            StepStatus::Failed
+        },
+        Some(format!(
+            "已迁移 {} 个，失败 {} 个",
+            report.migrated_packages.len(),
+            report.failed_packages.len()
+        )),
+    );
+
+    // 迁移别名
+    emit(UpgradeStep::Aliases, StepStatus::Running, None);
+    {
+        let _guard = lock_current_fnm_dir(Operation::new(OperationKind::Alias, &to)).await?;
+        let (old, new) = (from.clone(), to.clone());
+        report.moved_aliases = run_blocking(move || move_aliases(&ProcessRunner, &old, &new))
+            .await
+            .inspect_err(|e| {
+                emit(
+                    UpgradeStep::Aliases,
+                    StepStatus::Failed,
+                    Some(e.to_string()),
+                )
+            })?;
+    }
+    emit(
+        UpgradeStep::Aliases,
+        StepStatus::Succeeded,
+        Some(report.moved_aliases.join("、")),
+    );
+
+    // 卸载旧版本，仍有项目或进程使用时需要用户另行确认
+    if uninstall_old.unwrap_or(false) {
+        emit(UpgradeStep::Uninstall, StepStatus::Running, None);
+        match uninstall_version(jobs.clone(), projects.clone(), from.clone(), None).await {
+            Ok(_) => {
+                report.uninstalled = true;
+                emit(UpgradeStep::Uninstall, StepStatus::Succeeded, None);
+            }
+            Err(e) => {
+                emit(
+                    UpgradeStep::Uninstall,
+                    StepStatus::Failed,
+                    Some(e.to_string()),
+                );
+                report.uninstall_error = Some(e.to_string());
+            }
+        }
+    } else {
+        emit(UpgradeStep::Uninstall, StepStatus::Skipped, None);
+    }
+
+    Ok(report)
+}
+
+/// 发布线中已安装的最新版本
+pub fn newest_installed(dir: &FnmDir, line: &str) -> Option<String> {
+    dir.installed_versions()
+        .into_iter()
+        .filter(|version| release_line(version) == line)
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+/// 发布线中最新的远程版本
+pub fn latest_release<'a>(releases: &'a [RemoteRelease], line: &str) -> Option<&'a RemoteRelease> {
+    releases
+        .iter()
+        .filter(|release| release_line(&release.version) == line)
+        .max_by(|a, b| compare_versions(&a.version, &b.version))
+}
+
+/// 用新版本的 npm 重新安装旧版本中的全局包（保持原版本号）
+/// 自带的 npm、corepack 和通过 npm link 链接的包不迁移，新版本中已有的同版本包跳过
+pub fn replay_globals(old: &Path, new: &Path) -> (Vec<String>, Vec<PackageFailure>) {
+    let existing = global_packages(new);
+    let mut migrated = Vec::new();
+    let mut failed = Vec::new();
+
+    for package in global_packages(old) {
+        if package.bundled {
+            continue;
+        }
+        if package.linked {
+            failed.push(PackageFailure {
+                name: package.name,
+                error: "通过 npm link 链接的包需要手动重新链接".to_string(),
+            });
+            continue;
+        }
+        if existing
+            .iter()
+            .any(|e| e.name == package.name && e.version == package.version)
+        {
+            migrated.push(package.name);
+            continue;
+        }
+
+        match npm_install_global(new, &package) {
+            Ok(()) => migrated.push(package.name),
+            Err(e) => failed.push(PackageFailure {
+                name: package.name,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    (migrated, failed)
+}
+
+/// 使用指定版本自带的 npm 执行 npm install -g
+fn npm_install_global(installation: &Path, package: &GlobalPackage) -> Result<(), FnmGuiError> {
+    let bin = global_bin_dir(installation);
+    let npm = if cfg!(target_os = "windows") {
+        bin.join("npm.cmd")
+    } else {
+        bin.join("npm")
+    };
+    let spec = match package.version {
+        Some(ref version) => format!("{}@{}", package.name, version),
+        None => package.name.clone(),
+    };
+
+    // npm 通过 PATH 查找 node，需要让新版本的 bin 目录排在最前
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path =
+        std::env::join_paths(std::iter::once(bin.clone()).chain(std::env::split_paths(&path)))
+            .map_err(|e| FnmGuiError::Internal(e.to_string()))?;
+
+    let output = Command::new(&npm)
+        .args(["install", "--global", &spec])
+        .env("PATH", path)
+        .output()
+        .map(CommandOutput::from)
+        .map_err(|e| FnmGuiError::spawn("npm install", e))?;
+
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("npm install", None, &output));
+    }
+    Ok(())
+}
+
+/// 将指向 from 的别名（包括 default）改为指向 to，返回迁移的别名
+pub fn move_aliases(
+    runner: &dyn FnmRunner,
+    from: &str,
+    to: &str,
+) -> Result<Vec<String>, FnmGuiError> {
+    let dir = FnmDir::new(runner.fnm_dir()?);
+    let mut moved = Vec::new();
+
+    for alias in dir.aliases() {
+        if alias.dangling || alias.version.as_deref() != Some(from) {
+            continue;
+        }
+        let output = runner.run(&["alias", to, &alias.name])?;
+        if !output.is_success() {
+            return Err(FnmGuiError::from_output("fnm alias", Some(to), &output));
+        }
+        moved.push(alias.name);
+    }
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::remote::parse_index;
+    use super::super::runner::ScriptedRunner;
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 临时 fnm 目录，已安装 installed 中的版本
+    fn fixture(installed: &[&str]) -> (TempDir, FnmDir) {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = FnmDir::new(temp.path().join("fnm"));
+        for version in installed {
+            std::fs::create_dir_all(dir.installation_dir(version)).unwrap();
+        }
+        std::fs::create_dir_all(dir.aliases_dir()).unwrap();
+        (temp, dir)
+    }
+
+    #[cfg(unix)]
+    fn link(target: &Path, link: &Path) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    #[cfg(windows)]
+    fn link(target: &Path, link: &Path) {
+        std::os::windows::fs::symlink_dir(target, link).unwrap();
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn picks_the_latest_release_of_a_line() {
+        let releases = parse_index(
+            r#"[
+                {"version":"v21.0.0","date":"2023-10-17","lts":false,"files":[]},
+                {"version":"v20.10.0","date":"2023-11-22","lts":"Iron","files":[]},
+                {"version":"v20.9.0","date":"2023-10-24","lts":"Iron","files":[]},
+                {"version":"v0.12.18","date":"2017-02-22","lts":false,"files":[]},
+                {"version":"v0.10.48","date":"2016-10-18","lts":false,"files":[]}
+            ]"#,
+        )
+        .unwrap();
+
+        let latest = |line: &str| latest_release(&releases, line).map(|r| r.version.as_str());
+        assert_eq!(latest("20"), Some("v20.10.0"));
+        assert_eq!(latest("21"), Some("v21.0.0"));
+        assert_eq!(latest("0.12"), Some("v0.12.18"));
+        assert_eq!(latest("22"), None);
+    }
+
+    #[test]
+    fn moves_aliases_pointing_at_the_old_version() {
+        let (_temp, dir) = fixture(&["v18.20.8", "v20.11.0", "v20.12.2"]);
+        for (name, version) in [
+            ("default", "v20.11.0"),
+            ("work", "v20.11.0"),
+            ("legacy", "v18.20.8"),
+        ] {
+            link(
+                &dir.installation_dir(version),
+                &dir.aliases_dir().join(name),
+            );
+        }
+        let runner = ScriptedRunner::new(dir.root()).on(&["alias"], CommandOutput::success(""));
+
+        let mut moved = move_aliases(&runner, "v20.11.0", "v20.12.2").unwrap();
+        moved.sort();
+        assert_eq!(moved, ["default", "work"]);
+
+        let mut invocations = runner.invocations();
+        invocations.sort();
+        assert_eq!(
+            invocations,
+            [
+                args(&["alias", "v20.12.2", "default"]),
+                args(&["alias", "v20.12.2", "work"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_moving_aliases_when_fnm_fails() {
+        let (_temp, dir) = fixture(&["v20.11.0", "v20.12.2"]);
+        link(
+            &dir.installation_dir("v20.11.0"),
+            &dir.aliases_dir().join("default"),
+        );
+        let runner = ScriptedRunner::new(dir.root()).on(
+            &["alias"],
+            CommandOutput::failure(1, "error: permission denied"),
+        );
+
+        let e = move_aliases(&runner, "v20.11.0", "v20.12.2").unwrap_err();
+        assert_eq!(e.code(), "commandFailed");
+    }
+}
@@ -1,99 +1,296 @@
-use super::common::create_fnm_command;
+use super::env::read_fnm_env;
+use super::fnm_layout::FnmDir;
+use super::impact::check_uninstall;
+use super::jobs::{run_job, JobKind, JobManager};
+use super::operation::{lock_current_fnm_dir, Operation, OperationKind};
+use super::projects::ProjectRegistry;
+use super::remote::{RemoteFilter, RemoteIndexClient, RemoteRelease};
+use super::runner::{run_blocking, FnmRunner, ProcessRunner};
+use crate::error::FnmGuiError;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
-use tauri::command;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{command, State};
+
+/// 已安装的 Node 版本信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledVersion {
+    /// 版本号，如 v22.21.1；系统 Node 为 "system"
+    pub version: String,
+    pub is_default: bool,
+    /// 是否为 fnm list 中的 system 条目
+    pub is_system: bool,
+    /// 指向该版本的所有别名（不含 default）
+    pub aliases: Vec<String>,
+    /// LTS 代号，如 "iron"，从 lts-<codename> 别名推断
+    pub lts_codename: Option<String>,
+    pub install_path: Option<String>,
+    /// 从 node 可执行文件头部识别出的架构
+    pub arch: Option<String>,
+}
+
+/// 等待 fnm list 的最长时间，超时后直接读取 fnm 目录
+const FNM_LIST_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// 获取已安装的 Node 版本列表
 #[command]
-pub fn list_installed_versions() -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("list")
-        .output()
-        .map_err(|e| format!("执行 fnm list 失败: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+pub async fn list_installed_versions() -> Result<Vec<InstalledVersion>, FnmGuiError> {
+    let listed = tokio::time::timeout(
+        FNM_LIST_TIMEOUT,
+        run_blocking(|| list_installed(&ProcessRunner)),
+    )
+    .await;
+
+    match listed {
+        Ok(result) => result,
+        Err(_) => run_blocking(|| Ok(list_installed_from_disk(&FnmDir::current()?))).await,
     }
-
-    String::from_utf8(output.stdout).map_err(|e| e.to_string())
 }
 
-/// 获取远程可用版本列表
-#[command]
-pub fn list_remote_versions(lts_only: bool, filter: Option<String>) -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    cmd.arg("list-remote");
-    cmd.arg("--sort").arg("desc"); // 最新版本排在前面
-
-    if lts_only {
-        cmd.arg("--lts");
+/// 通过 fnm list 获取已安装版本，并补充安装路径和架构信息
+/// fnm 不可用时改为直接读取 fnm 目录
+pub fn list_installed(runner: &dyn FnmRunner) -> Result<Vec<InstalledVersion>, FnmGuiError> {
+    let output = match runner.run(&["list"]) {
+        Ok(output) => output,
+        Err(e) if e.is_fnm_unavailable() => {
+            return Ok(list_installed_from_disk(&FnmDir::new(runner.fnm_dir()?)));
+        }
+        Err(e) => return Err(e),
+    };
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("fnm list", None, &output));
     }
 
-    // 支持服务端过滤
-    if let Some(ref keyword) = filter {
-        if !keyword.is_empty() {
-            cmd.arg("--filter").arg(keyword);
+    let mut versions = parse_installed_versions(&output.stdout);
+
+    if let Ok(fnm_dir) = runner.fnm_dir() {
+        for version in versions.iter_mut().filter(|v| !v.is_system) {
+            let installation = fnm_dir
+                .join("node-versions")
+                .join(&version.version)
+                .join("installation");
+            version.arch = detect_node_arch(&installation);
+            version.install_path = Some(installation.to_string_lossy().to_string());
         }
     }
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("执行 fnm list-remote 失败: {}", e))?;
+    Ok(versions)
+}
 
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
+/// 根据 fnm 目录中的版本和别名构建已安装版本列表，不包含 system
+pub fn list_installed_from_disk(dir: &FnmDir) -> Vec<InstalledVersion> {
+    let aliases = dir.aliases();
+
+    dir.installed_versions()
+        .into_iter()
+        .map(|version| {
+            let tags: Vec<String> = aliases
+                .iter()
+                .filter(|alias| alias.version.as_deref() == Some(version.as_str()))
+                .map(|alias| alias.name.clone())
+                .collect();
+            let installation = dir.installation_dir(&version);
+
+            InstalledVersion {
+                is_default: tags.iter().any(|t| t == "default"),
+                is_system: false,
+                lts_codename: lts_codename(&tags),
+                aliases: tags.into_iter().filter(|t| t != "default").collect(),
+                arch: detect_node_arch(&installation),
+                install_path: Some(installation.to_string_lossy().to_string()),
+                version,
+            }
+        })
+        .collect()
+}
 
-    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+/// 解析 fnm list 命令的输出
+/// 输出格式示例（当前版本会带有 ANSI 颜色）：
+/// * v22.21.1 default
+/// * v20.12.2 lts-iron, lts-latest
+/// * v18.20.8
+/// * system
+pub fn parse_installed_versions(output: &str) -> Vec<InstalledVersion> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = strip_ansi(line);
+            let line = line.trim();
+            let line = line.strip_prefix('*').unwrap_or(line).trim();
+
+            let mut parts = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|s| !s.is_empty());
+            let version = parts.next()?.to_string();
+            let tags: Vec<String> = parts.map(|s| s.to_string()).collect();
+
+            let is_system = version == "system";
+            if !is_system && !version.starts_with('v') {
+                return None;
+            }
+
+            Some(InstalledVersion {
+                version,
+                is_default: tags.iter().any(|t| t == "default"),
+                is_system,
+                lts_codename: lts_codename(&tags),
+                aliases: tags.into_iter().filter(|t| t != "default").collect(),
+                install_path: None,
+                arch: None,
+            })
+        })
+        .collect()
 }
 
-/// 安装指定版本
-#[command]
-pub async fn install_version(version: String) -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("install")
-        .arg(&version)
-        .output()
-        .map_err(|e| format!("执行 fnm install 失败: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+/// 从 lts-<codename> 别名推断 LTS 代号
+fn lts_codename(aliases: &[String]) -> Option<String> {
+    aliases
+        .iter()
+        .filter_map(|tag| tag.strip_prefix("lts-").or_else(|| tag.strip_prefix("lts/")))
+        .find(|name| *name != "latest" && *name != "*")
+        .map(|name| name.to_string())
+}
+
+/// 去除 ANSI 转义序列（如颜色代码）
+fn strip_ansi(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            result.push(c);
+            continue;
+        }
+        // CSI 序列: ESC [ 参数 ... 终止字符 (0x40-0x7E)
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
     }
 
-    Ok(format!("成功安装 {}", version))
+    result
 }
 
-/// 卸载指定版本
+/// 读取 node 可执行文件的头部，识别其目标架构
+fn detect_node_arch(installation: &Path) -> Option<String> {
+    #[cfg(windows)]
+    let node_bin = installation.join("node.exe");
+    #[cfg(not(windows))]
+    let node_bin = installation.join("bin").join("node");
+
+    let mut header = [0u8; 1024];
+    let mut file = fs::File::open(node_bin).ok()?;
+    let len = file.read(&mut header).ok()?;
+    let header = &header[..len];
+
+    let u16_le = |offset: usize| {
+        header
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let u32_le = |offset: usize| {
+        header
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let arch = match header.get(..4)? {
+        // ELF: e_machine 位于偏移 18
+        [0x7f, b'E', b'L', b'F'] => match u16_le(18)? {
+            0x3e => "x64",
+            0xb7 => "arm64",
+            0x03 => "x86",
+            0x28 => "armv7l",
+            0x15 => "ppc64le",
+            0x16 => "s390x",
+            _ => return None,
+        },
+        // Mach-O 64 位（小端）: cputype 位于偏移 4
+        [0xcf, 0xfa, 0xed, 0xfe] => match u32_le(4)? {
+            0x0100_0007 => "x64",
+            0x0100_000c => "arm64",
+            _ => return None,
+        },
+        // PE: 0x3c 处为 PE 头偏移，Machine 字段紧随 "PE\0\0" 签名
+        [b'M', b'Z', ..] => {
+            let pe_offset = u32_le(0x3c)? as usize;
+            match u16_le(pe_offset + 4)? {
+                0x8664 => "x64",
+                0xaa64 => "arm64",
+                0x014c => "x86",
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(arch.to_string())
+}
+
+/// 获取远程可用版本列表，可按 LTS、安全更新、架构和版本前缀筛选
 #[command]
-pub fn uninstall_version(version: String) -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("uninstall")
-        .arg(&version)
-        .output()
-        .map_err(|e| format!("执行 fnm uninstall 失败: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
+pub async fn list_remote_versions(
+    lts_only: bool,
+    security_only: Option<bool>,
+    arch: Option<String>,
+    filter: Option<String>,
+) -> Result<Vec<RemoteRelease>, FnmGuiError> {
+    let env = run_blocking(|| read_fnm_env(&ProcessRunner)).await?;
+    let client = RemoteIndexClient::new(&env.node_dist_mirror);
+
+    client
+        .list_releases(&RemoteFilter {
+            lts_only,
+            security_only: security_only.unwrap_or(false),
+            arch,
+            keyword: filter,
+        })
+        .await
+}
 
+/// 卸载指定版本，作为任务执行并记录在任务历史中
+/// 该版本是默认版本、被别名或项目引用、或有进程正在运行时，需要传入 confirmed 确认
+#[command]
+pub async fn uninstall_version(
+    jobs: State<'_, JobManager>,
+    projects: State<'_, ProjectRegistry>,
+    version: String,
+    confirmed: Option<bool>,
+) -> Result<String, FnmGuiError> {
+    let projects = projects.list();
+    let target = version.clone();
+    run_blocking(move || {
+        check_uninstall(
+            &FnmDir::current()?,
+            &target,
+            &projects,
+            confirmed.unwrap_or(false),
+        )
+    })
+    .await?;
+
+    run_job(&ProcessRunner, jobs.inner(), JobKind::Uninstall, &version, &()).await?;
     Ok(format!("成功卸载 {}", version))
 }
 
 /// 切换使用的 Node 版本
 #[command]
-pub fn use_version(version: String) -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("use")
-        .arg(&version)
-        .output()
-        .map_err(|e| format!("执行 fnm use 失败: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+pub async fn use_version(version: String) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::new(OperationKind::Use, &version)).await?;
+    run_blocking(move || use_node(&ProcessRunner, &version)).await
+}
+
+pub fn use_node(runner: &dyn FnmRunner, version: &str) -> Result<String, FnmGuiError> {
+    let output = runner.run(&["use", version])?;
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("fnm use", Some(version), &output));
     }
 
     Ok(format!("已切换到 {}", version))
@@ -101,102 +298,95 @@ pub fn use_version(version: String) -> Result<String, String> {
 
 /// 获取当前使用的 Node 版本
 #[command]
-pub fn get_current_version() -> Result<String, String> {
+pub async fn get_current_version() -> Result<String, FnmGuiError> {
+    run_blocking(|| current_version(&ProcessRunner)).await
+}
+
+pub fn current_version(runner: &dyn FnmRunner) -> Result<String, FnmGuiError> {
     // 首先尝试 fnm current
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("current")
-        .output()
-        .map_err(|e| format!("执行 fnm current 失败: {}", e))?;
-
-    if output.status.success() {
-        return String::from_utf8(output.stdout)
-            .map(|s| s.trim().to_string())
-            .map_err(|e| e.to_string());
+    match runner.run(&["current"]) {
+        Ok(output) if output.is_success() => return Ok(output.stdout.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.is_fnm_unavailable() => {}
+        Err(e) => return Err(e),
     }
 
-    // 如果 fnm current 失败（通常在 GUI 环境中），尝试读取 default 别名
-    get_default_version_from_alias()
+    // 如果 fnm current 失败（通常在 GUI 环境中）或 fnm 不可用，读取 default 别名；
+    // 返回 "none" 表示没有设置默认版本
+    Ok(FnmDir::new(runner.fnm_dir()?)
+        .default_version()
+        .unwrap_or_else(|| "none".to_string()))
 }
 
-/// 从 fnm 别名目录读取默认版本
-fn get_default_version_from_alias() -> Result<String, String> {
-    let fnm_dir = get_fnm_dir();
-    if fnm_dir.is_empty() {
-        return Err("无法确定 fnm 目录".to_string());
-    }
-
-    let default_alias = PathBuf::from(&fnm_dir).join("aliases").join("default");
+/// 设置默认 Node 版本
+#[command]
+pub async fn set_default_version(version: String) -> Result<String, FnmGuiError> {
+    let _guard = lock_current_fnm_dir(Operation::new(OperationKind::SetDefault, &version)).await?;
+    run_blocking(move || set_default(&ProcessRunner, &version)).await
+}
 
-    if default_alias.exists() {
-        // default 是一个符号链接，指向实际版本目录
-        match fs::read_link(&default_alias) {
-            Ok(target) => {
-                // 从路径中提取版本号
-                if let Some(version) = target.file_name() {
-                    return Ok(version.to_string_lossy().to_string());
-                }
-            }
-            Err(_) => {
-                // 如果不是符号链接，尝试直接读取
-                if let Ok(content) = fs::read_to_string(&default_alias) {
-                    return Ok(content.trim().to_string());
-                }
-            }
-        }
+pub fn set_default(runner: &dyn FnmRunner, version: &str) -> Result<String, FnmGuiError> {
+    let output = runner.run(&["default", version])?;
+    if !output.is_success() {
+        return Err(FnmGuiError::from_output("fnm default", Some(version), &output));
     }
 
-    // 返回 "none" 表示没有设置默认版本
-    Ok("none".to_string())
+    Ok(format!("已将 {} 设为默认版本", version))
 }
 
-/// 获取 fnm 目录路径
-fn get_fnm_dir() -> String {
-    let home = dirs::home_dir();
-
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join("Library/Application Support/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// fnm list 的实际输出：当前版本整行为青色，带有 default 和 lts 别名
+    const FNM_LIST: &str = "* v18.20.8\n\
+        * v20.12.2 lts-iron\n\
+        \u{1b}[36m* v22.21.1 default, lts-jod, lts-latest\u{1b}[39m\n\
+        * system\n";
+
+    #[test]
+    fn strip_ansi_removes_color_codes() {
+        assert_eq!(strip_ansi("\u{1b}[36m* v22.21.1\u{1b}[39m"), "* v22.21.1");
+        assert_eq!(strip_ansi("\u{1b}[1;32mdefault\u{1b}[0m"), "default");
+        assert_eq!(strip_ansi("* v20.12.2 lts-iron"), "* v20.12.2 lts-iron");
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(local_appdata) = std::env::var("LOCALAPPDATA") {
-            return format!("{}\\fnm", local_appdata);
-        }
+    #[test]
+    fn parses_versions_aliases_and_system() {
+        let versions = parse_installed_versions(FNM_LIST);
+        let names: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(names, ["v18.20.8", "v20.12.2", "v22.21.1", "system"]);
+
+        let iron = &versions[1];
+        assert!(!iron.is_default);
+        assert_eq!(iron.aliases, ["lts-iron"]);
+        assert_eq!(iron.lts_codename.as_deref(), Some("iron"));
+
+        let current = &versions[2];
+        assert!(current.is_default);
+        assert_eq!(current.aliases, ["lts-jod", "lts-latest"]);
+        assert_eq!(current.lts_codename.as_deref(), Some("jod"));
+
+        let system = &versions[3];
+        assert!(system.is_system);
+        assert!(!system.is_default);
+        assert!(system.aliases.is_empty());
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home_dir) = home {
-            return home_dir
-                .join(".local/share/fnm")
-                .to_string_lossy()
-                .to_string();
-        }
+    #[test]
+    fn colored_default_marker_is_recognized() {
+        let versions = parse_installed_versions("* v20.12.2 \u{1b}[32mdefault\u{1b}[39m\n");
+        assert_eq!(versions.len(), 1);
+        assert!(versions[0].is_default);
+        assert!(versions[0].aliases.is_empty());
     }
 
-    String::new()
-}
-
-/// 设置默认 Node 版本
-#[command]
-pub fn set_default_version(version: String) -> Result<String, String> {
-    let mut cmd = create_fnm_command()?;
-    let output = cmd
-        .arg("default")
-        .arg(&version)
-        .output()
-        .map_err(|e| format!("执行 fnm default 失败: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    #[test]
+    fn ignores_blank_and_unrelated_lines() {
+        let output = "\nerror: something went wrong\n* v16.20.2\n\n";
+        let versions = parse_installed_versions(output);
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "v16.20.2");
+        assert!(parse_installed_versions("").is_empty());
     }
-
-    Ok(format!("已将 {} 设为默认版本", version))
 }
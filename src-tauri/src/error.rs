@@ -0,0 +1,210 @@
+use crate::commands::runner::CommandOutput;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// 后端 commands 的统一错误类型
+/// 序列化为 { code, message, details }，前端可根据 code 区分处理
+#[derive(Debug, Error)]
+pub enum FnmGuiError {
+    #[error("未找到 fnm，请确保已正确安装 fnm。\n\n安装方式：\nmacOS: brew install fnm\nWindows: winget install Schniz.fnm\nLinux: curl -fsSL https://fnm.vercel.app/install | bash")]
+    FnmNotFound,
+
+    #[error("无法确定 fnm 目录")]
+    FnmDirUnresolvable,
+
+    #[error("无法获取 HOME 目录")]
+    HomeDirUnavailable,
+
+    #[error("执行 {command} 失败: {source}")]
+    CommandSpawn {
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{command} 执行失败: {stderr}")]
+    CommandFailed {
+        command: String,
+        stderr: String,
+        code: Option<i32>,
+    },
+
+    #[error("Node {0} 尚未安装")]
+    VersionNotInstalled(String),
+
+    #[error("请先设置一个默认 Node.js 版本")]
+    NoDefaultVersion,
+
+    #[error("找不到 corepack，请确保 Node.js 版本 >= 16.9.0")]
+    CorepackMissing,
+
+    #[error("别名 {0} 不存在")]
+    AliasNotFound(String),
+
+    #[error("别名 {0} 已存在")]
+    AliasExists(String),
+
+    #[error("找不到 node 进程 {0}")]
+    ProcessNotFound(u32),
+
+    #[error("找不到任务 {0}")]
+    JobNotFound(String),
+
+    #[error("已取消 {0}")]
+    Cancelled(String),
+
+    #[error("无法{operation}：{running} 正在进行中")]
+    OperationConflict { operation: String, running: String },
+
+    #[error("{operation} 需要确认：{}", .reasons.join("；"))]
+    ConfirmationRequired {
+        operation: String,
+        reasons: Vec<String>,
+    },
+
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("请求 {url} 失败: {message}")]
+    Network { url: String, message: String },
+
+    #[error("解析 {what} 失败: {message}")]
+    Parse { what: String, message: String },
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("内部错误: {0}")]
+    Internal(String),
+}
+
+impl FnmGuiError {
+    /// 带上下文说明的 IO 错误，如 "读取 ~/.bashrc 失败"
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        FnmGuiError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn spawn(command: impl Into<String>, source: std::io::Error) -> Self {
+        FnmGuiError::CommandSpawn {
+            command: command.into(),
+            source,
+        }
+    }
+
+    pub fn parse(what: impl Into<String>, message: impl ToString) -> Self {
+        FnmGuiError::Parse {
+            what: what.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// 根据执行失败的进程输出构造错误，识别 fnm 的"版本未安装"和"找不到版本"提示
+    pub fn from_output(
+        command: impl Into<String>,
+        version: Option<&str>,
+        output: &CommandOutput,
+    ) -> Self {
+        let stderr = output.stderr.trim().to_string();
+
+        if let Some(version) = version {
+            let lower = stderr.to_lowercase();
+            if lower.contains("not currently installed")
+                || lower.contains("is not installed")
+                || lower.contains("can't find requested version")
+            {
+                return FnmGuiError::VersionNotInstalled(version.to_string());
+            }
+        }
+
+        FnmGuiError::CommandFailed {
+            command: command.into(),
+            stderr,
+            code: output.code,
+        }
+    }
+
+    /// 是否因为找不到或无法启动 fnm 而失败，此时可以改为直接读取 fnm 目录
+    pub fn is_fnm_unavailable(&self) -> bool {
+        matches!(
+            self,
+            FnmGuiError::FnmNotFound | FnmGuiError::CommandSpawn { .. }
+        )
+    }
+
+    /// 供前端区分错误类型的代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            FnmGuiError::FnmNotFound => "fnmNotFound",
+            FnmGuiError::FnmDirUnresolvable => "fnmDirUnresolvable",
+            FnmGuiError::HomeDirUnavailable => "homeDirUnavailable",
+            FnmGuiError::CommandSpawn { .. } => "commandSpawn",
+            FnmGuiError::CommandFailed { .. } => "commandFailed",
+            FnmGuiError::VersionNotInstalled(_) => "versionNotInstalled",
+            FnmGuiError::NoDefaultVersion => "noDefaultVersion",
+            FnmGuiError::CorepackMissing => "corepackMissing",
+            FnmGuiError::AliasNotFound(_) => "aliasNotFound",
+            FnmGuiError::AliasExists(_) => "aliasExists",
+            FnmGuiError::ProcessNotFound(_) => "processNotFound",
+            FnmGuiError::JobNotFound(_) => "jobNotFound",
+            FnmGuiError::Cancelled(_) => "cancelled",
+            FnmGuiError::OperationConflict { .. } => "operationConflict",
+            FnmGuiError::ConfirmationRequired { .. } => "confirmationRequired",
+            FnmGuiError::Io { .. } => "io",
+            FnmGuiError::Network { .. } => "network",
+            FnmGuiError::Parse { .. } => "parse",
+            FnmGuiError::InvalidInput(_) => "invalidInput",
+            FnmGuiError::Internal(_) => "internal",
+        }
+    }
+
+    /// 附加的结构化信息
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            FnmGuiError::CommandFailed {
+                command,
+                stderr,
+                code,
+            } => Some(serde_json::json!({ "command": command, "stderr": stderr, "code": code })),
+            FnmGuiError::CommandSpawn { command, source } => {
+                Some(serde_json::json!({ "command": command, "error": source.to_string() }))
+            }
+            FnmGuiError::VersionNotInstalled(version) => {
+                Some(serde_json::json!({ "version": version }))
+            }
+            FnmGuiError::AliasNotFound(name) | FnmGuiError::AliasExists(name) => {
+                Some(serde_json::json!({ "alias": name }))
+            }
+            FnmGuiError::ProcessNotFound(pid) => Some(serde_json::json!({ "pid": pid })),
+            FnmGuiError::JobNotFound(job_id) => Some(serde_json::json!({ "jobId": job_id })),
+            FnmGuiError::ConfirmationRequired { operation, reasons } => {
+                Some(serde_json::json!({ "operation": operation, "reasons": reasons }))
+            }
+            FnmGuiError::Io { source, .. } => {
+                Some(serde_json::json!({ "kind": format!("{:?}", source.kind()) }))
+            }
+            FnmGuiError::Network { url, .. } => Some(serde_json::json!({ "url": url })),
+            FnmGuiError::OperationConflict { operation, running } => {
+                Some(serde_json::json!({ "operation": operation, "running": running }))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for FnmGuiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FnmGuiError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
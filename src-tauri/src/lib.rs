@@ -1,28 +1,95 @@
-mod commands;
+pub mod commands;
+pub mod error;
 
+use commands::alias::*;
 use commands::common::*;
+use commands::disk_usage::*;
 use commands::env::*;
 use commands::fs::*;
+use commands::global_packages::*;
+use commands::impact::*;
+use commands::install::*;
+use commands::jobs::*;
+use commands::processes::*;
+use commands::project_version::*;
+use commands::projects::*;
+use commands::prune::*;
+use commands::semver::*;
+use commands::settings::init_app_settings;
+use commands::shell_profile::*;
+use commands::upgrade::*;
 use commands::version::*;
+use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            init_app_settings(app.path().app_config_dir()?)?;
+
+            let handle = app.handle().clone();
+            let jobs = JobManager::load(app.path().app_data_dir()?).with_notifier(move |job| {
+                let _ = handle.emit(JOB_UPDATED_EVENT, job);
+            });
+            app.manage(jobs);
+            app.manage(ProjectRegistry::load(app.path().app_data_dir()?));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // 版本管理 commands
             list_installed_versions,
             list_remote_versions,
             install_version,
+            cancel_install,
             uninstall_version,
+            analyze_uninstall,
             use_version,
             get_current_version,
             set_default_version,
+            upgrade_version,
+            list_global_packages,
+            // 别名 commands
+            list_aliases,
+            create_alias,
+            delete_alias,
+            rename_alias,
+            repair_aliases,
+            // 磁盘占用 commands
+            get_disk_usage,
+            // 清理 commands
+            get_prune_policy,
+            set_prune_policy,
+            preview_prune,
+            apply_prune,
+            // 进程 commands
+            list_node_processes,
+            terminate_node_process,
+            // 项目 commands
+            add_project,
+            remove_project,
+            scan_projects,
+            resolve_project_version,
+            pin_project_version,
+            get_version_sources,
+            set_version_sources,
+            resolve_version_spec,
+            // 任务 commands
+            list_jobs,
+            get_job,
+            cancel_job,
+            retry_job,
             // 环境变量 commands
             get_fnm_env,
+            set_fnm_env,
             toggle_corepack,
+            // shell 配置集成 commands
+            get_shell_integrations,
+            install_shell_integration,
+            remove_shell_integration,
             // 文件系统 commands
             get_fnm_dir,
+            get_fnm_dir_info,
             get_version_dir,
             open_version_directory,
             open_fnm_directory,
@@ -0,0 +1,315 @@
+//! 通过 ScriptedRunner 模拟 fnm，测试版本管理 commands 对 fnm 输出和退出码的处理
+
+use fnm_gui_lib::commands::install::ProgressReporter;
+use fnm_gui_lib::commands::jobs::{
+    execute_job, run_job, JobKind, JobManager, JobObserver, JobRecord, JobStatus,
+};
+use fnm_gui_lib::commands::operation::{lock_fnm_dir, Operation, OperationKind};
+use fnm_gui_lib::commands::runner::{CommandOutput, ScriptedRunner};
+use fnm_gui_lib::commands::version::{current_version, list_installed, set_default, use_node};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// 创建包含已安装版本的 fnm 目录，default 指向 default 参数对应的版本
+fn fnm_dir(versions: &[&str], default: Option<&str>) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for version in versions {
+        std::fs::create_dir_all(installation(dir.path(), version).join("bin")).unwrap();
+    }
+    std::fs::create_dir_all(dir.path().join("aliases")).unwrap();
+    if let Some(version) = default {
+        link(
+            &installation(dir.path(), version),
+            &dir.path().join("aliases/default"),
+        );
+    }
+    dir
+}
+
+fn installation(dir: &Path, version: &str) -> std::path::PathBuf {
+    dir.join("node-versions").join(version).join("installation")
+}
+
+#[cfg(unix)]
+fn link(target: &Path, alias: &Path) {
+    std::os::unix::fs::symlink(target, alias).unwrap();
+}
+
+#[cfg(windows)]
+fn link(target: &Path, alias: &Path) {
+    std::os::windows::fs::symlink_dir(target, alias).unwrap();
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// 记录任务回调中的状态和输出行
+#[derive(Default)]
+struct Recorder {
+    statuses: Mutex<Vec<JobStatus>>,
+    lines: Mutex<Vec<String>>,
+}
+
+impl JobObserver for Recorder {
+    fn on_update(&self, job: &JobRecord) {
+        self.statuses.lock().unwrap().push(job.status);
+    }
+
+    fn on_line(&self, line: &str) {
+        self.lines.lock().unwrap().push(line.to_string());
+    }
+}
+
+#[test]
+fn list_adds_install_path_to_fnm_list_output() {
+    let dir = fnm_dir(&["v20.12.2", "v22.21.1"], Some("v22.21.1"));
+    let runner = ScriptedRunner::new(dir.path()).on(
+        &["list"],
+        CommandOutput::success(
+            "* v20.12.2 lts-iron\n\u{1b}[36m* v22.21.1 default\u{1b}[39m\n* system\n",
+        ),
+    );
+
+    let versions = list_installed(&runner).unwrap();
+    let names: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(names, ["v20.12.2", "v22.21.1", "system"]);
+    assert!(versions[1].is_default);
+    assert_eq!(
+        versions[0].install_path.as_deref(),
+        Some(installation(dir.path(), "v20.12.2").to_str().unwrap())
+    );
+    assert_eq!(versions[2].install_path, None);
+    assert_eq!(runner.invocations(), [args(&["list"])]);
+}
+
+#[test]
+fn list_reads_fnm_dir_when_fnm_is_unavailable() {
+    let dir = fnm_dir(&["v18.20.8", "v20.12.2"], Some("v20.12.2"));
+    let runner = ScriptedRunner::new(dir.path()).unavailable();
+
+    let versions = list_installed(&runner).unwrap();
+    let names: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(names, ["v18.20.8", "v20.12.2"]);
+    assert!(!versions[0].is_default);
+    assert!(versions[1].is_default);
+}
+
+#[test]
+fn list_reports_fnm_failure() {
+    let dir = fnm_dir(&[], None);
+    let runner =
+        ScriptedRunner::new(dir.path()).on(&["list"], CommandOutput::failure(1, "error: boom"));
+
+    assert_eq!(list_installed(&runner).unwrap_err().code(), "commandFailed");
+}
+
+#[tokio::test]
+async fn install_streams_output_into_the_job() {
+    let dir = fnm_dir(&[], None);
+    let runner = ScriptedRunner::new(dir.path()).on(
+        &["install"],
+        CommandOutput {
+            stdout: "Installing Node v22.21.1 (x64)\n\n".to_string(),
+            stderr: "  00:00:01 [####] 100%\n".to_string(),
+            code: Some(0),
+        },
+    );
+    let jobs = JobManager::default();
+    let recorder = Recorder::default();
+
+    let job = run_job(&runner, &jobs, JobKind::Install, "22", &recorder)
+        .await
+        .unwrap();
+
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.exit_code, Some(0));
+    assert_eq!(job.args, args(&["install", "22"]));
+    assert_eq!(runner.invocations(), [args(&["install", "22"])]);
+    assert_eq!(
+        *recorder.lines.lock().unwrap(),
+        ["Installing Node v22.21.1 (x64)", "00:00:01 [####] 100%"]
+    );
+    assert_eq!(
+        *recorder.statuses.lock().unwrap(),
+        [JobStatus::Queued, JobStatus::Running, JobStatus::Succeeded]
+    );
+    assert!(job.output.contains("Installing Node v22.21.1"));
+}
+
+#[tokio::test]
+async fn install_failure_marks_the_job_failed() {
+    let dir = fnm_dir(&[], None);
+    let runner = ScriptedRunner::new(dir.path()).on(
+        &["install"],
+        CommandOutput::failure(1, "error: Can't find version that matches 99"),
+    );
+    let jobs = JobManager::default();
+
+    let e = run_job(&runner, &jobs, JobKind::Install, "99", &())
+        .await
+        .unwrap_err();
+
+    assert_eq!(e.code(), "commandFailed");
+    let job = &jobs.list()[0];
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(job.exit_code, Some(1));
+    assert!(job.error.is_some());
+}
+
+#[tokio::test]
+async fn install_fails_when_fnm_is_unavailable() {
+    let dir = fnm_dir(&[], None);
+    let runner = ScriptedRunner::new(dir.path()).unavailable();
+    let jobs = JobManager::default();
+
+    let e = run_job(&runner, &jobs, JobKind::Install, "22", &())
+        .await
+        .unwrap_err();
+
+    assert_eq!(e.code(), "fnmNotFound");
+    assert_eq!(jobs.list()[0].status, JobStatus::Failed);
+}
+
+#[tokio::test]
+async fn cancelling_a_queued_install_keeps_the_existing_installation() {
+    let dir = fnm_dir(&["v20.12.2"], None);
+    let runner = ScriptedRunner::new(dir.path()).on(&["install"], CommandOutput::success(""));
+    let jobs = JobManager::default();
+    let reporter =
+        ProgressReporter::new(Arc::new(|_| {}), "20.12.2", Some(dir.path().to_path_buf()));
+
+    // 另一个操作持有 fnm 目录锁，安装任务只能排队
+    let _running = lock_fnm_dir(
+        dir.path().to_path_buf(),
+        Operation::new(OperationKind::Uninstall, "v18.20.8"),
+    )
+    .await
+    .unwrap();
+    let (job, cancel_rx) = jobs.create(JobKind::Install, "20.12.2", None);
+    jobs.cancel(&job.id).unwrap();
+
+    let e = execute_job(&runner, &jobs, job, cancel_rx, &reporter)
+        .await
+        .unwrap_err();
+
+    assert_eq!(e.code(), "cancelled");
+    assert_eq!(jobs.list()[0].status, JobStatus::Cancelled);
+    assert!(runner.invocations().is_empty());
+    assert!(installation(dir.path(), "v20.12.2").is_dir());
+}
+
+#[tokio::test]
+async fn uninstall_runs_as_a_job() {
+    let dir = fnm_dir(&["v18.20.8"], None);
+    let runner = ScriptedRunner::new(dir.path())
+        .on(&["uninstall", "v18.20.8"], CommandOutput::success(""))
+        .on(
+            &["uninstall"],
+            CommandOutput::failure(1, "error: Version v9 is not installed"),
+        );
+    let jobs = JobManager::default();
+
+    let job = run_job(&runner, &jobs, JobKind::Uninstall, "v18.20.8", &())
+        .await
+        .unwrap();
+    assert_eq!(job.status, JobStatus::Succeeded);
+
+    let e = run_job(&runner, &jobs, JobKind::Uninstall, "v9", &())
+        .await
+        .unwrap_err();
+    assert_eq!(e.code(), "versionNotInstalled");
+    assert_eq!(
+        runner.invocations(),
+        [args(&["uninstall", "v18.20.8"]), args(&["uninstall", "v9"])]
+    );
+}
+
+#[test]
+fn use_switches_version() {
+    let dir = fnm_dir(&["v20.12.2"], None);
+    let runner = ScriptedRunner::new(dir.path())
+        .on(
+            &["use", "v20.12.2"],
+            CommandOutput::success("Using Node v20.12.2\n"),
+        )
+        .on(
+            &["use"],
+            CommandOutput::failure(1, "error: Requested version v9 is not currently installed"),
+        );
+
+    assert_eq!(use_node(&runner, "v20.12.2").unwrap(), "已切换到 v20.12.2");
+    assert_eq!(
+        use_node(&runner, "v9").unwrap_err().code(),
+        "versionNotInstalled"
+    );
+    assert_eq!(
+        runner.invocations(),
+        [args(&["use", "v20.12.2"]), args(&["use", "v9"])]
+    );
+}
+
+#[test]
+fn default_sets_the_default_alias() {
+    let dir = fnm_dir(&["v20.12.2"], None);
+    let runner = ScriptedRunner::new(dir.path())
+        .on(&["default", "v20.12.2"], CommandOutput::success(""))
+        .on(
+            &["default"],
+            CommandOutput::failure(1, "error: Can't find requested version: v9"),
+        );
+
+    assert_eq!(
+        set_default(&runner, "v20.12.2").unwrap(),
+        "已将 v20.12.2 设为默认版本"
+    );
+    assert_eq!(
+        set_default(&runner, "v9").unwrap_err().code(),
+        "versionNotInstalled"
+    );
+}
+
+#[test]
+fn current_version_prefers_fnm_current() {
+    let dir = fnm_dir(&["v20.12.2", "v22.21.1"], Some("v20.12.2"));
+    let runner =
+        ScriptedRunner::new(dir.path()).on(&["current"], CommandOutput::success("v22.21.1\n"));
+
+    assert_eq!(current_version(&runner).unwrap(), "v22.21.1");
+}
+
+#[test]
+fn current_version_falls_back_to_default_alias() {
+    let dir = fnm_dir(&["v20.12.2"], Some("v20.12.2"));
+
+    // GUI 进程中没有 fnm env 设置的环境变量，fnm current 会失败
+    let failing = ScriptedRunner::new(dir.path()).on(
+        &["current"],
+        CommandOutput::failure(
+            1,
+            "error: We can't find the necessary environment variables",
+        ),
+    );
+    assert_eq!(current_version(&failing).unwrap(), "v20.12.2");
+
+    let unavailable = ScriptedRunner::new(dir.path()).unavailable();
+    assert_eq!(current_version(&unavailable).unwrap(), "v20.12.2");
+}
+
+#[test]
+fn current_version_is_none_without_a_usable_default() {
+    let missing = fnm_dir(&["v20.12.2"], None);
+    let runner = ScriptedRunner::new(missing.path()).unavailable();
+    assert_eq!(current_version(&runner).unwrap(), "none");
+
+    // default 指向已卸载的版本
+    let dangling = fnm_dir(&[], None);
+    link(
+        &installation(dangling.path(), "v16.0.0"),
+        &dangling.path().join("aliases/default"),
+    );
+    let runner = ScriptedRunner::new(dangling.path())
+        .on(&["current"], CommandOutput::failure(1, "error: no"));
+    assert_eq!(current_version(&runner).unwrap(), "none");
+}
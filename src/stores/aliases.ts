@@ -0,0 +1,75 @@
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { AliasEntry } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const useAliasesStore = defineStore('aliases', () => {
+  // 状态
+  const aliases = ref<AliasEntry[]>([])
+  const error = ref<string | null>(null)
+
+  // 计算属性
+  const danglingAliases = computed(() => aliases.value.filter(a => a.dangling))
+
+  // 获取全部别名
+  async function fetchAliases() {
+    try {
+      aliases.value = await invoke<AliasEntry[]>('list_aliases')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch aliases:', e)
+    }
+  }
+
+  // 执行别名操作后刷新列表
+  async function mutate(command: string, args: Record<string, unknown>): Promise<boolean> {
+    error.value = null
+
+    try {
+      await invoke(command, args)
+      await fetchAliases()
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error(`Failed to run ${command}:`, e)
+      return false
+    }
+  }
+
+  // 创建或覆盖别名
+  function createAlias(name: string, version: string): Promise<boolean> {
+    return mutate('create_alias', { name, version })
+  }
+
+  // 删除别名
+  function deleteAlias(name: string): Promise<boolean> {
+    return mutate('delete_alias', { name })
+  }
+
+  // 重命名别名
+  function renameAlias(from: string, to: string): Promise<boolean> {
+    return mutate('rename_alias', { from, to })
+  }
+
+  // 删除指向已卸载版本的别名
+  function repairAliases(): Promise<boolean> {
+    return mutate('repair_aliases', {})
+  }
+
+  return {
+    // 状态
+    aliases,
+    error,
+
+    // 计算属性
+    danglingAliases,
+
+    // 方法
+    fetchAliases,
+    createAlias,
+    deleteAlias,
+    renameAlias,
+    repairAliases
+  }
+})
@@ -1,2 +1,8 @@
 export * from './version'
 export * from './settings'
+export * from './jobs'
+export * from './aliases'
+export * from './processes'
+export * from './storage'
+export * from './packages'
+export * from './projects'
@@ -0,0 +1,86 @@
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import type { JobRecord } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const useJobsStore = defineStore('jobs', () => {
+  // 状态，最新的任务在前
+  const jobs = ref<JobRecord[]>([])
+  const error = ref<string | null>(null)
+
+  listen<JobRecord>('job-updated', event => {
+    const job = event.payload
+    const index = jobs.value.findIndex(j => j.id === job.id)
+    if (index >= 0) {
+      jobs.value.splice(index, 1, job)
+    } else {
+      jobs.value.unshift(job)
+    }
+  })
+
+  // 计算属性
+  const activeJobs = computed(() => {
+    return jobs.value.filter(j => j.status === 'queued' || j.status === 'running')
+  })
+
+  // 获取任务历史
+  async function fetchJobs() {
+    try {
+      jobs.value = await invoke<JobRecord[]>('list_jobs')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch jobs:', e)
+    }
+  }
+
+  // 获取单个任务（包括执行中任务的输出）
+  async function getJob(jobId: string): Promise<JobRecord | null> {
+    try {
+      return await invoke<JobRecord>('get_job', { jobId })
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to get job:', e)
+      return null
+    }
+  }
+
+  // 取消任务
+  async function cancelJob(jobId: string): Promise<boolean> {
+    try {
+      await invoke('cancel_job', { jobId })
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to cancel job:', e)
+      return false
+    }
+  }
+
+  // 重试任务，新任务在后台执行
+  async function retryJob(jobId: string): Promise<JobRecord | null> {
+    try {
+      return await invoke<JobRecord>('retry_job', { jobId })
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to retry job:', e)
+      return null
+    }
+  }
+
+  return {
+    // 状态
+    jobs,
+    error,
+
+    // 计算属性
+    activeJobs,
+
+    // 方法
+    fetchJobs,
+    getJob,
+    cancelJob,
+    retryJob
+  }
+})
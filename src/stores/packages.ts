@@ -0,0 +1,50 @@
+import { defineStore } from 'pinia'
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { VersionGlobalPackages } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const usePackagesStore = defineStore('packages', () => {
+  // 状态：按版本记录全局包
+  const packagesByVersion = ref<Record<string, VersionGlobalPackages>>({})
+  const loading = ref(false)
+  const error = ref<string | null>(null)
+
+  // 获取全局包，不传 version 时获取所有已安装版本
+  async function fetchGlobalPackages(version?: string) {
+    loading.value = true
+    error.value = null
+
+    try {
+      const result = await invoke<VersionGlobalPackages[]>('list_global_packages', { version })
+      const next = version ? { ...packagesByVersion.value } : {}
+      for (const entry of result) {
+        next[entry.version] = entry
+      }
+      packagesByVersion.value = next
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch global packages:', e)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  // 缺少指定包的版本，用于检查标准工具是否齐全
+  function versionsMissing(packageName: string): string[] {
+    return Object.values(packagesByVersion.value)
+      .filter(entry => !entry.packages.some(p => p.name === packageName))
+      .map(entry => entry.version)
+  }
+
+  return {
+    // 状态
+    packagesByVersion,
+    loading,
+    error,
+
+    // 方法
+    fetchGlobalPackages,
+    versionsMissing
+  }
+})
@@ -0,0 +1,65 @@
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { NodeProcess } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const useProcessesStore = defineStore('processes', () => {
+  // 状态
+  const processes = ref<NodeProcess[]>([])
+  const loading = ref(false)
+  const error = ref<string | null>(null)
+
+  // 计算属性：按版本分组
+  const processesByVersion = computed(() => {
+    const groups: Record<string, NodeProcess[]> = {}
+    for (const process of processes.value) {
+      ;(groups[process.version] ??= []).push(process)
+    }
+    return groups
+  })
+
+  // 获取运行中的 node 进程
+  async function fetchProcesses() {
+    loading.value = true
+    error.value = null
+
+    try {
+      processes.value = await invoke<NodeProcess[]>('list_node_processes')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch node processes:', e)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  // 结束进程，force 为 true 时强制结束
+  async function terminateProcess(pid: number, force = false): Promise<boolean> {
+    error.value = null
+
+    try {
+      await invoke('terminate_node_process', { pid, force })
+      await fetchProcesses()
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to terminate process:', e)
+      return false
+    }
+  }
+
+  return {
+    // 状态
+    processes,
+    loading,
+    error,
+
+    // 计算属性
+    processesByVersion,
+
+    // 方法
+    fetchProcesses,
+    terminateProcess
+  }
+})
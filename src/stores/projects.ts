@@ -0,0 +1,159 @@
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { PinFormat, ProjectEntry, ProjectStatus, ProjectVersion, VersionResolution, VersionSource, VersionSpecSource } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const useProjectsStore = defineStore('projects', () => {
+  // 状态
+  const projects = ref<ProjectStatus[]>([])
+  const versions = ref<Record<string, ProjectVersion>>({})
+  const versionSources = ref<VersionSource[]>([])
+  const loading = ref(false)
+  const error = ref<string | null>(null)
+
+  // 计算属性：需要处理的项目（缺少版本或 default 不满足）
+  const nonCompliantProjects = computed(() =>
+    projects.value.filter(p => p.spec && (!p.installed || p.defaultSatisfies === false))
+  )
+
+  // 检查所有项目的版本要求
+  async function scanProjects() {
+    loading.value = true
+    error.value = null
+
+    try {
+      projects.value = await invoke<ProjectStatus[]>('scan_projects')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to scan projects:', e)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  // 加入项目，recursive 为 true 时加入目录下找到的所有项目
+  async function addProject(path: string, recursive = false): Promise<ProjectEntry[]> {
+    error.value = null
+
+    try {
+      const added = await invoke<ProjectEntry[]>('add_project', { path, recursive })
+      await scanProjects()
+      return added
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to add project:', e)
+      return []
+    }
+  }
+
+  // 从列表中移除项目
+  async function removeProject(path: string): Promise<boolean> {
+    error.value = null
+
+    try {
+      await invoke('remove_project', { path })
+      projects.value = projects.value.filter(p => p.path !== path)
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to remove project:', e)
+      return false
+    }
+  }
+
+  // 解析项目目录使用的 Node 版本
+  async function resolveProjectVersion(path: string): Promise<ProjectVersion | null> {
+    error.value = null
+
+    try {
+      const result = await invoke<ProjectVersion>('resolve_project_version', { path })
+      versions.value = { ...versions.value, [path]: result }
+      return result
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to resolve project version:', e)
+      return null
+    }
+  }
+
+  // 将项目固定到指定版本，已有不一致的版本声明时需要 force 才会写入
+  async function pinProjectVersion(
+    path: string,
+    version: string,
+    format: PinFormat,
+    force = false
+  ): Promise<VersionSpecSource | null> {
+    error.value = null
+
+    try {
+      const source = await invoke<VersionSpecSource>('pin_project_version', { path, version, format, force })
+      await scanProjects()
+      return source
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to pin project version:', e)
+      return null
+    }
+  }
+
+  // 获取项目版本来源的优先级
+  async function fetchVersionSources() {
+    try {
+      versionSources.value = await invoke<VersionSource[]>('get_version_sources')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch version sources:', e)
+    }
+  }
+
+  // 保存项目版本来源的优先级，传入 null 时恢复默认顺序
+  async function saveVersionSources(sources: VersionSource[] | null): Promise<boolean> {
+    error.value = null
+
+    try {
+      versionSources.value = await invoke<VersionSource[]>('set_version_sources', { sources })
+      await scanProjects()
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to save version sources:', e)
+      return false
+    }
+  }
+
+  // 将版本写法解析为已安装版本，remote 为 true 时同时查询远程最新匹配版本
+  async function resolveVersionSpec(spec: string, remote = false): Promise<VersionResolution | null> {
+    error.value = null
+
+    try {
+      return await invoke<VersionResolution>('resolve_version_spec', { spec, remote })
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to resolve version spec:', e)
+      return null
+    }
+  }
+
+  return {
+    // 状态
+    projects,
+    versions,
+    versionSources,
+    loading,
+    error,
+
+    // 计算属性
+    nonCompliantProjects,
+
+    // 方法
+    scanProjects,
+    addProject,
+    removeProject,
+    pinProjectVersion,
+    fetchVersionSources,
+    saveVersionSources,
+    resolveProjectVersion,
+    resolveVersionSpec
+  }
+})
@@ -1,7 +1,8 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
-import type { FnmEnv } from '@/types/fnm'
+import type { FnmDirInfo, FnmEnv, FnmEnvOverrides, FnmEnvSources } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
 
 export const useSettingsStore = defineStore('settings', () => {
   // 状态
@@ -9,9 +10,11 @@ export const useSettingsStore = defineStore('settings', () => {
   const nodeDistMirror = ref('https://nodejs.org/dist')
   const versionFileStrategy = ref<'local' | 'recursive'>('local')
   const corepackEnabled = ref(false)
+  const corepackShims = ref(false)
   const resolveEngines = ref(true)
   const arch = ref('')
   const loglevel = ref('info')
+  const sources = ref<FnmEnvSources | null>(null)
   const loading = ref(false)
   const error = ref<string | null>(null)
 
@@ -23,29 +26,50 @@ export const useSettingsStore = defineStore('settings', () => {
     { label: '华为镜像', value: 'https://mirrors.huaweicloud.com/nodejs' }
   ]
 
+  function applyEnv(env: FnmEnv) {
+    fnmDir.value = env.fnmDir
+    nodeDistMirror.value = env.nodeDistMirror
+    versionFileStrategy.value = env.versionFileStrategy as 'local' | 'recursive'
+    corepackEnabled.value = env.corepackEnabled
+    corepackShims.value = env.corepackShims
+    resolveEngines.value = env.resolveEngines
+    arch.value = env.arch
+    loglevel.value = env.loglevel
+    sources.value = env.sources
+  }
+
   // 加载设置
   async function loadSettings() {
     loading.value = true
     error.value = null
 
     try {
-      const env = await invoke<FnmEnv>('get_fnm_env')
-
-      fnmDir.value = env.fnmDir
-      nodeDistMirror.value = env.nodeDistMirror
-      versionFileStrategy.value = env.versionFileStrategy as 'local' | 'recursive'
-      corepackEnabled.value = env.corepackEnabled
-      resolveEngines.value = env.resolveEngines
-      arch.value = env.arch
-      loglevel.value = env.loglevel
+      applyEnv(await invoke<FnmEnv>('get_fnm_env'))
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to load settings:', e)
     } finally {
       loading.value = false
     }
   }
 
+  // 保存设置，影响之后所有的 fnm 操作
+  async function saveSettings(overrides: FnmEnvOverrides): Promise<boolean> {
+    loading.value = true
+    error.value = null
+
+    try {
+      applyEnv(await invoke<FnmEnv>('set_fnm_env', { overrides }))
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to save settings:', e)
+      return false
+    } finally {
+      loading.value = false
+    }
+  }
+
   // 获取 fnm 目录路径
   async function getFnmDir(): Promise<string> {
     try {
@@ -56,13 +80,23 @@ export const useSettingsStore = defineStore('settings', () => {
     }
   }
 
+  // 获取 fnm 目录及其来源
+  async function getFnmDirInfo(): Promise<FnmDirInfo | null> {
+    try {
+      return await invoke<FnmDirInfo>('get_fnm_dir_info')
+    } catch (e) {
+      console.error('Failed to get fnm dir info:', e)
+      return null
+    }
+  }
+
   // 打开 fnm 目录
   async function openFnmDirectory(): Promise<boolean> {
     try {
       await invoke('open_fnm_directory')
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to open fnm directory:', e)
       return false
     }
@@ -74,9 +108,11 @@ export const useSettingsStore = defineStore('settings', () => {
     nodeDistMirror,
     versionFileStrategy,
     corepackEnabled,
+    corepackShims,
     resolveEngines,
     arch,
     loglevel,
+    sources,
     loading,
     error,
 
@@ -85,7 +121,9 @@ export const useSettingsStore = defineStore('settings', () => {
 
     // 方法
     loadSettings,
+    saveSettings,
     getFnmDir,
+    getFnmDirInfo,
     openFnmDirectory
   }
 })
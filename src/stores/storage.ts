@@ -0,0 +1,103 @@
+import { defineStore } from 'pinia'
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { DiskUsageReport, PrunePlan, PrunePolicy, PruneResult } from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+
+export const useStorageStore = defineStore('storage', () => {
+  // 状态
+  const usage = ref<DiskUsageReport | null>(null)
+  const prunePolicy = ref<PrunePolicy | null>(null)
+  const prunePlan = ref<PrunePlan | null>(null)
+  const loading = ref(false)
+  const error = ref<string | null>(null)
+
+  // 统计磁盘占用，需要遍历各版本目录，耗时较长
+  async function fetchDiskUsage() {
+    loading.value = true
+    error.value = null
+
+    try {
+      usage.value = await invoke<DiskUsageReport>('get_disk_usage')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch disk usage:', e)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  // 获取保存的清理策略
+  async function fetchPrunePolicy() {
+    try {
+      prunePolicy.value = await invoke<PrunePolicy>('get_prune_policy')
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to fetch prune policy:', e)
+    }
+  }
+
+  // 保存清理策略
+  async function savePrunePolicy(policy: PrunePolicy): Promise<boolean> {
+    error.value = null
+
+    try {
+      prunePolicy.value = await invoke<PrunePolicy>('set_prune_policy', { policy })
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to save prune policy:', e)
+      return false
+    }
+  }
+
+  // 预览清理结果，不传 policy 时使用保存的策略
+  async function previewPrune(policy?: PrunePolicy) {
+    loading.value = true
+    error.value = null
+
+    try {
+      prunePlan.value = await invoke<PrunePlan>('preview_prune', { policy })
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to preview prune:', e)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  // 卸载预览中列出的版本，预览之后不再满足清理条件的版本会出现在 failed 中
+  async function applyPrune(plan: PrunePlan): Promise<PruneResult | null> {
+    loading.value = true
+    error.value = null
+
+    try {
+      const versions = plan.remove.map(entry => entry.version)
+      const result = await invoke<PruneResult>('apply_prune', { versions, policy: plan.policy })
+      prunePlan.value = null
+      return result
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to apply prune:', e)
+      return null
+    } finally {
+      loading.value = false
+    }
+  }
+
+  return {
+    // 状态
+    usage,
+    prunePolicy,
+    prunePlan,
+    loading,
+    error,
+
+    // 方法
+    fetchDiskUsage,
+    fetchPrunePolicy,
+    savePrunePolicy,
+    previewPrune,
+    applyPrune
+  }
+})
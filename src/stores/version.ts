@@ -1,8 +1,18 @@
 import { defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
-import type { NodeVersion } from '@/types/fnm'
-import { parseInstalledVersions, parseRemoteVersions, filterVersions, compareVersions } from '@/utils/version-parser'
+import { listen } from '@tauri-apps/api/event'
+import type {
+  InstallProgress,
+  InstalledVersion,
+  NodeVersion,
+  RemoteRelease,
+  UninstallImpact,
+  UpgradeProgress,
+  UpgradeReport
+} from '@/types/fnm'
+import { getErrorMessage } from '@/utils/error'
+import { toInstalledNodeVersions, toRemoteNodeVersions, filterVersions, compareVersions } from '@/utils/version-parser'
 
 export const useVersionStore = defineStore('version', () => {
   // 状态
@@ -12,6 +22,26 @@ export const useVersionStore = defineStore('version', () => {
   const loading = ref(false)
   const remoteLoading = ref(false)
   const error = ref<string | null>(null)
+  // 按版本记录最近一次安装进度
+  const installProgress = ref<Record<string, InstallProgress>>({})
+  // 按发布线记录升级的各步骤状态
+  const upgradeProgress = ref<Record<string, UpgradeProgress[]>>({})
+
+  listen<InstallProgress>('install-progress', event => {
+    installProgress.value = {
+      ...installProgress.value,
+      [event.payload.version]: event.payload
+    }
+  })
+
+  listen<UpgradeProgress>('upgrade-progress', event => {
+    const progress = event.payload
+    const steps = (upgradeProgress.value[progress.line] ?? []).filter(s => s.step !== progress.step)
+    upgradeProgress.value = {
+      ...upgradeProgress.value,
+      [progress.line]: [...steps, progress]
+    }
+  })
 
   // 计算属性
   const sortedInstalledVersions = computed(() => {
@@ -33,14 +63,14 @@ export const useVersionStore = defineStore('version', () => {
 
     try {
       const [listResult, current] = await Promise.all([
-        invoke<string>('list_installed_versions'),
+        invoke<InstalledVersion[]>('list_installed_versions'),
         invoke<string>('get_current_version')
       ])
 
       currentVersion.value = current.trim()
-      installedVersions.value = parseInstalledVersions(listResult, currentVersion.value)
+      installedVersions.value = toInstalledNodeVersions(listResult, currentVersion.value)
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to fetch installed versions:', e)
     } finally {
       loading.value = false
@@ -48,20 +78,27 @@ export const useVersionStore = defineStore('version', () => {
   }
 
   // 获取远程版本
-  async function fetchRemoteVersions(options?: { lts?: boolean; filter?: string }) {
+  async function fetchRemoteVersions(options?: {
+    lts?: boolean
+    security?: boolean
+    arch?: string
+    filter?: string
+  }) {
     remoteLoading.value = true
     error.value = null
 
     try {
-      const result = await invoke<string>('list_remote_versions', {
+      const result = await invoke<RemoteRelease[]>('list_remote_versions', {
         ltsOnly: options?.lts ?? false,
+        securityOnly: options?.security ?? false,
+        arch: options?.arch || null,
         filter: options?.filter || null
       })
 
       const installedNames = installedVersions.value.map(v => v.name)
-      remoteVersions.value = parseRemoteVersions(result, installedNames)
+      remoteVersions.value = toRemoteNodeVersions(result, installedNames)
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to fetch remote versions:', e)
     } finally {
       remoteLoading.value = false
@@ -84,7 +121,7 @@ export const useVersionStore = defineStore('version', () => {
       }))
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to install version:', e)
       return false
     } finally {
@@ -92,13 +129,41 @@ export const useVersionStore = defineStore('version', () => {
     }
   }
 
-  // 卸载版本
-  async function uninstallVersion(version: string): Promise<boolean> {
+  // 取消安装
+  async function cancelInstall(version: string): Promise<boolean> {
+    const progress = installProgress.value[version]
+    if (!progress) return false
+
+    try {
+      await invoke('cancel_install', { jobId: progress.jobId })
+      return true
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to cancel install:', e)
+      return false
+    }
+  }
+
+  // 分析卸载的影响（默认版本、别名、项目、运行中的进程、可释放空间）
+  async function analyzeUninstall(version: string): Promise<UninstallImpact | null> {
+    error.value = null
+
+    try {
+      return await invoke<UninstallImpact>('analyze_uninstall', { version })
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to analyze uninstall:', e)
+      return null
+    }
+  }
+
+  // 卸载版本，存在风险时需要 confirmed 为 true
+  async function uninstallVersion(version: string, confirmed = false): Promise<boolean> {
     loading.value = true
     error.value = null
 
     try {
-      await invoke('uninstall_version', { version })
+      await invoke('uninstall_version', { version, confirmed })
       await fetchInstalledVersions()
       // 更新远程版本的安装状态
       const installedNames = installedVersions.value.map(v => v.name)
@@ -108,7 +173,7 @@ export const useVersionStore = defineStore('version', () => {
       }))
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to uninstall version:', e)
       return false
     } finally {
@@ -116,6 +181,22 @@ export const useVersionStore = defineStore('version', () => {
     }
   }
 
+  // 升级到发布线的最新补丁版本，迁移全局包和别名
+  async function upgradeVersion(major: string, uninstallOld = false): Promise<UpgradeReport | null> {
+    error.value = null
+    upgradeProgress.value = { ...upgradeProgress.value, [major]: [] }
+
+    try {
+      const report = await invoke<UpgradeReport>('upgrade_version', { major, uninstallOld })
+      await fetchInstalledVersions()
+      return report
+    } catch (e) {
+      error.value = getErrorMessage(e)
+      console.error('Failed to upgrade version:', e)
+      return null
+    }
+  }
+
   // 切换版本
   async function useVersion(version: string): Promise<boolean> {
     error.value = null
@@ -130,7 +211,7 @@ export const useVersionStore = defineStore('version', () => {
       }))
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to use version:', e)
       return false
     }
@@ -149,7 +230,7 @@ export const useVersionStore = defineStore('version', () => {
       }))
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to set default version:', e)
       return false
     }
@@ -163,7 +244,7 @@ export const useVersionStore = defineStore('version', () => {
       await invoke('open_version_directory', { version })
       return true
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = getErrorMessage(e)
       console.error('Failed to open version directory:', e)
       return false
     }
@@ -190,6 +271,8 @@ export const useVersionStore = defineStore('version', () => {
     loading,
     remoteLoading,
     error,
+    installProgress,
+    upgradeProgress,
 
     // 计算属性
     sortedInstalledVersions,
@@ -200,7 +283,10 @@ export const useVersionStore = defineStore('version', () => {
     fetchInstalledVersions,
     fetchRemoteVersions,
     installVersion,
+    cancelInstall,
+    analyzeUninstall,
     uninstallVersion,
+    upgradeVersion,
     useVersion,
     setDefaultVersion,
     openVersionDirectory,
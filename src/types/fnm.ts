@@ -9,15 +9,61 @@ export interface NodeVersion {
   aliases: string[]      // 别名列表
 }
 
+// list_installed_versions 返回的已安装版本信息
+export interface InstalledVersion {
+  version: string              // 版本号，如 v22.21.1，系统 Node 为 "system"
+  isDefault: boolean           // 是否为默认版本
+  isSystem: boolean            // 是否为 system 条目
+  aliases: string[]            // 别名列表（不含 default）
+  ltsCodename?: string         // LTS 代号，如 "iron"
+  installPath?: string         // 安装目录
+  arch?: string                // 架构 (arm64/x64)
+}
+
+// list_remote_versions 返回的远程版本信息（来自镜像 index.json）
+export interface RemoteRelease {
+  version: string        // 版本号，如 v22.21.1
+  date: string           // 发布日期
+  lts?: string           // LTS 名称，如 "Jod"
+  security: boolean      // 是否为安全更新
+  npm?: string           // 附带的 npm 版本
+  v8?: string            // V8 版本
+  openssl?: string       // OpenSSL 版本
+  files: string[]        // 可用的平台文件
+}
+
 // fnm 环境变量配置
 export interface FnmEnv {
   fnmDir: string                    // fnm 安装目录
   nodeDistMirror: string            // Node 下载镜像
   versionFileStrategy: 'local' | 'recursive'  // 版本文件策略
-  corepackEnabled: boolean          // 是否启用 Corepack
+  corepackEnabled: boolean          // FNM_COREPACK_ENABLED：安装新版本后自动启用 Corepack
+  corepackShims: boolean            // default 版本中是否已执行 corepack enable
   resolveEngines: boolean           // 是否解析 engines 字段
   arch: string                      // 架构 (arm64/x64)
   loglevel: string                  // 日志级别
+  sources: FnmEnvSources            // 各项配置的来源
+}
+
+// set_fnm_env 的参数：应用内覆盖的 fnm 配置，未设置的项沿用 fnm 自身配置
+export interface FnmEnvOverrides {
+  fnmDir?: string                   // 优先于 FNM_DIR 环境变量和 fnm 的默认目录
+  nodeDistMirror?: string
+  versionFileStrategy?: 'local' | 'recursive'
+  resolveEngines?: boolean
+  arch?: string
+  loglevel?: string
+}
+
+// 配置值来源：fnm 默认值 / shell 配置文件 / 应用进程的环境变量 / 应用内覆盖
+export type EnvValueSource = 'fnmDefault' | 'shellProfile' | 'envVar' | 'appOverride'
+
+export type FnmEnvSources = Record<Exclude<keyof FnmEnv, 'sources' | 'corepackShims'>, EnvValueSource>
+
+// fnm 目录及其来源
+export interface FnmDirInfo {
+  path: string
+  source: 'appOverride' | 'envVar' | 'shellProfile' | 'fnmEnv' | 'legacy' | 'xdgDataHome' | 'platformDefault'
 }
 
 // 版本筛选选项
@@ -34,10 +80,257 @@ export interface OperationResult {
   data?: unknown
 }
 
-// 安装进度事件
+// 安装进度事件（install-progress）
 export interface InstallProgress {
+  jobId: string
   version: string
-  status: 'downloading' | 'extracting' | 'installing' | 'completed' | 'failed'
+  status: 'queued' | 'downloading' | 'extracting' | 'installing' | 'completed' | 'failed' | 'cancelled'
   progress?: number
   message?: string
 }
+
+// 后台任务（job-updated 事件）
+// list_aliases 返回的别名
+export interface AliasEntry {
+  name: string
+  kind: 'symlink' | 'file'          // 符号链接或记录目标的普通文件
+  target: string                    // 原始链接目标
+  version?: string                  // 指向的版本，如 v20.12.2
+  dangling: boolean                 // 目标版本已被删除
+}
+
+// analyze_uninstall 返回的卸载影响
+export type UninstallRisk = 'defaultVersion' | 'aliases' | 'projects' | 'runningProcesses'
+
+export interface NodeProcess {
+  pid: number
+  version: string
+  exe: string
+  command: string                   // 完整命令行
+  cwd?: string                      // 工作目录
+  startedAt?: number                // 启动时间，Unix 毫秒时间戳
+  ports: number[]                   // 正在监听的 TCP 端口
+}
+
+export interface UninstallImpact {
+  version: string                   // 实际会被卸载的版本
+  installPath: string
+  isDefault: boolean
+  aliases: string[]                 // 指向该版本的别名（不含 default）
+  projects: { path: string; file: string; spec: string }[]
+  processes: NodeProcess[]
+  reclaimableBytes: number          // 可释放的空间（字节）
+  risks: UninstallRisk[]
+  requiresConfirmation: boolean
+}
+
+// get_disk_usage 返回的磁盘占用（字节）
+export interface VersionDiskUsage {
+  version: string
+  totalBytes: number
+  baseBytes: number                 // Node 本身（含自带的 npm、corepack）
+  globalPackagesBytes: number       // npm install -g 安装的全局包
+}
+
+export interface CacheDiskUsage {
+  path: string
+  exists: boolean
+  bytes: number
+}
+
+export interface DiskUsageReport {
+  fnmDir: string
+  versions: VersionDiskUsage[]
+  versionsBytes: number
+  multishells?: CacheDiskUsage
+  corepackCache?: CacheDiskUsage
+  npmCache?: CacheDiskUsage
+  totalBytes: number
+}
+
+// 旧版本清理策略
+export interface PrunePolicy {
+  keepLatestPerMajor?: number | null       // 每个主版本保留最新的 N 个补丁版本
+  removeUnusedAfterDays?: number | null    // 清理超过 N 天未使用的版本
+  keepReferenced: boolean                  // 保留被别名或项目引用的版本
+}
+
+export interface PruneEntry {
+  version: string
+  reasons: string[]                 // 清理或保留的原因
+  lastUsedAt?: number               // 最近一次使用时间，Unix 毫秒时间戳
+  bytes: number
+}
+
+export interface PrunePlan {
+  policy: PrunePolicy
+  remove: PruneEntry[]
+  keep: PruneEntry[]
+  reclaimableBytes: number
+}
+
+export interface PruneResult {
+  removed: string[]
+  failed: { version: string; error: string }[]
+}
+
+// upgrade_version 的进度事件和结果
+export type UpgradeStep = 'resolve' | 'install' | 'globals' | 'aliases' | 'uninstall'
+export type UpgradeStepStatus = 'running' | 'succeeded' | 'failed' | 'skipped'
+
+export interface UpgradeProgress {
+  line: string                      // 发布线，如 "20"
+  step: UpgradeStep
+  status: UpgradeStepStatus
+  message?: string
+}
+
+export interface UpgradeReport {
+  line: string
+  from: string
+  to: string
+  upToDate: boolean                 // 已经是最新版本，未做任何修改
+  migratedPackages: string[]
+  failedPackages: { name: string; error: string }[]
+  movedAliases: string[]
+  uninstalled: boolean
+  uninstallError?: string           // 卸载旧版本失败或需要确认的原因
+}
+
+// list_global_packages 返回的全局 npm 包
+export interface GlobalPackage {
+  name: string                      // 包名，如 @vue/cli
+  version?: string
+  bundled: boolean                  // 随 Node 一起安装的 npm、corepack
+  linked: boolean                   // 通过 npm link 链接的包
+  bins: string[]                    // 提供的命令
+}
+
+export interface VersionGlobalPackages {
+  version: string
+  packages: GlobalPackage[]
+}
+
+// 项目中声明 Node 版本的位置，顺序即查找优先级
+export type VersionSource = 'nvmrc' | 'nodeVersion' | 'engines' | 'toolVersions' | 'volta' | 'devEngines'
+
+// 项目列表与 scan_projects 返回的项目版本检查结果
+export interface ProjectEntry {
+  path: string
+  addedAt: number                   // 加入时间，Unix 毫秒时间戳
+}
+
+export interface ProjectStatus extends ProjectEntry {
+  exists: boolean                   // 项目目录是否仍然存在
+  file?: string                     // 声明版本的文件
+  source?: VersionSource            // 生效的版本来源
+  spec?: string                     // 声明的版本
+  resolved?: string                 // 匹配的已安装版本
+  installed: boolean
+  defaultVersion?: string           // default 指向的版本
+  defaultSatisfies?: boolean        // default 是否满足声明的版本
+}
+
+// pin_project_version 写入的位置
+export type PinFormat = 'nodeVersion' | 'nvmrc' | 'engines' | 'volta'
+
+// resolve_project_version 返回的项目版本
+export interface VersionSpecSource {
+  file: string                      // 声明版本的文件路径
+  spec: string                      // 原始写法，如 20、lts/iron
+  source: VersionSource
+}
+
+export interface ProjectVersion {
+  path: string
+  strategy: 'local' | 'recursive'   // FNM_VERSION_FILE_STRATEGY
+  source?: VersionSpecSource
+  sources: VersionSource[]          // 使用的来源优先级
+  resolved?: string                 // 解析出的已安装版本
+  installRequired: boolean          // 需要先安装匹配的版本
+}
+
+// resolve_version_spec 返回的版本写法解析结果
+export type VersionSpecKind = 'latest' | 'lts' | 'range' | 'alias'
+
+export interface VersionResolution {
+  spec: string                      // 原始写法，如 >=18 <21、lts/iron
+  kind: VersionSpecKind
+  installed?: string                // 匹配的已安装版本
+  remote?: string                   // 匹配的远程最新版本
+}
+
+export type JobKind = 'install' | 'uninstall'
+export type JobStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled' | 'interrupted'
+
+export interface JobRecord {
+  id: string
+  kind: JobKind
+  version: string
+  args: string[]                    // 传给 fnm 的参数
+  status: JobStatus
+  createdAt: number                 // Unix 毫秒时间戳
+  startedAt?: number
+  endedAt?: number
+  exitCode?: number
+  output: string                    // stdout 与 stderr 合并后的输出
+  error?: string
+  retryOf?: string                  // 由哪个任务重试而来
+}
+
+// shell 配置文件集成
+export type Shell = 'bash' | 'zsh' | 'fish'
+
+export interface ShellIntegrationOptions {
+  useOnCd: boolean                  // --use-on-cd
+  versionFileStrategy?: 'local' | 'recursive'
+  corepackEnabled: boolean
+  resolveEngines?: boolean
+  envVars: Record<string, string>   // 额外导出的环境变量
+}
+
+export interface ShellIntegrationStatus {
+  shell: Shell
+  profilePath: string
+  installed: boolean                // shell 是否已安装
+  isLoginShell: boolean             // 是否为 $SHELL
+  profileExists: boolean
+  managed: boolean                  // 是否存在 fnm-gui 管理区块
+  manualSetup: boolean              // 区块外是否已有手动配置的 fnm env
+  managedBlock?: string
+}
+
+export interface ShellProfileChange {
+  shell: Shell
+  profilePath: string
+  backupPath?: string               // 修改前的备份文件
+}
+
+// 后端 command 返回的错误
+export type FnmGuiErrorCode =
+  | 'fnmNotFound'
+  | 'fnmDirUnresolvable'
+  | 'homeDirUnavailable'
+  | 'commandSpawn'
+  | 'commandFailed'
+  | 'versionNotInstalled'
+  | 'noDefaultVersion'
+  | 'corepackMissing'
+  | 'aliasNotFound'
+  | 'aliasExists'
+  | 'processNotFound'
+  | 'jobNotFound'
+  | 'cancelled'
+  | 'operationConflict'
+  | 'confirmationRequired'
+  | 'io'
+  | 'network'
+  | 'parse'
+  | 'invalidInput'
+  | 'internal'
+
+export interface FnmGuiError {
+  code: FnmGuiErrorCode
+  message: string
+  details?: Record<string, unknown> | null   // 如 commandFailed 的 stderr、退出码
+}
@@ -0,0 +1,18 @@
+import type { FnmGuiError } from '@/types/fnm'
+
+/**
+ * 判断是否为后端返回的结构化错误
+ */
+export function isFnmGuiError(e: unknown): e is FnmGuiError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e
+}
+
+/**
+ * 获取错误的展示文本
+ */
+export function getErrorMessage(e: unknown): string {
+  if (isFnmGuiError(e) || e instanceof Error) {
+    return e.message
+  }
+  return String(e)
+}
@@ -1,74 +1,39 @@
-import type { NodeVersion } from '@/types/fnm'
+import type { InstalledVersion, NodeVersion, RemoteRelease } from '@/types/fnm'
 
 /**
- * 解析 fnm list 命令的输出
- * 输出格式示例：
- * * v22.21.1 default
- * * v20.12.2 lts-latest
- * * v18.20.8
- * * system
+ * 将后端返回的已安装版本转换为界面使用的 NodeVersion
  */
-export function parseInstalledVersions(output: string, currentVersion: string): NodeVersion[] {
-  const lines = output.split('\n').filter(line => line.trim())
-
-  return lines
-    .filter(line => !line.includes('system')) // 过滤掉 system
-    .map(line => {
-      const cleanLine = line.replace(/^\*\s*/, '').trim()
-      const parts = cleanLine.split(/\s+/)
-      const name = parts[0]
-      const tags = parts.slice(1)
-
-      const isDefault = tags.includes('default')
-      const isLts = tags.some(tag => tag.toLowerCase().includes('lts'))
-      const ltsName = tags.find(tag => tag.toLowerCase().includes('lts'))
+export function toInstalledNodeVersions(versions: InstalledVersion[], currentVersion: string): NodeVersion[] {
+  return versions
+    .filter(v => !v.isSystem)
+    .map(v => {
+      const ltsAlias = v.aliases.find(a => a.toLowerCase().startsWith('lts'))
 
       return {
-        name,
+        name: v.version,
         isInstalled: true,
-        isDefault,
-        isCurrent: name === currentVersion,
-        isLts,
-        ltsName,
-        aliases: tags.filter(t => t !== 'default' && !t.toLowerCase().includes('lts'))
+        isDefault: v.isDefault,
+        isCurrent: v.version === currentVersion,
+        isLts: !!v.ltsCodename || !!ltsAlias,
+        ltsName: v.ltsCodename ?? ltsAlias,
+        aliases: v.aliases.filter(a => !a.toLowerCase().startsWith('lts'))
       }
     })
 }
 
 /**
- * 解析 fnm list-remote 命令的输出
- * 输出格式示例：
- * v22.21.1
- * v22.21.0
- * v20.12.2 (Jod)
+ * 将后端返回的远程版本转换为界面使用的 NodeVersion
  */
-export function parseRemoteVersions(output: string, installedVersions: string[]): NodeVersion[] {
-  const lines = output.split('\n').filter(line => line.trim())
-
-  const results: NodeVersion[] = []
-
-  for (const line of lines) {
-    const cleanLine = line.trim()
-    // 匹配版本号和可能的 LTS 名称，如 "v20.12.2 (Jod)"
-    const match = cleanLine.match(/^(v[\d.]+)(?:\s+\(([^)]+)\))?/)
-
-    if (match) {
-      const name = match[1]
-      const ltsName = match[2]
-
-      results.push({
-        name,
-        isInstalled: installedVersions.includes(name),
-        isDefault: false,
-        isCurrent: false,
-        isLts: !!ltsName,
-        ltsName,
-        aliases: []
-      })
-    }
-  }
-
-  return results
+export function toRemoteNodeVersions(releases: RemoteRelease[], installedVersions: string[]): NodeVersion[] {
+  return releases.map(release => ({
+    name: release.version,
+    isInstalled: installedVersions.includes(release.version),
+    isDefault: false,
+    isCurrent: false,
+    isLts: !!release.lts,
+    ltsName: release.lts,
+    aliases: []
+  }))
 }
 
 /**
@@ -77,17 +42,43 @@ export function parseRemoteVersions(output: string, installedVersions: string[])
  */
 export function compareVersions(a: string, b: string): number {
   const parseVersion = (v: string) => {
-    const match = v.match(/v?(\d+)\.(\d+)\.(\d+)/)
-    if (!match) return [0, 0, 0]
-    return [parseInt(match[1]), parseInt(match[2]), parseInt(match[3])]
+    const match = v.match(/v?(\d+)\.(\d+)\.(\d+)(?:-([0-9A-Za-z.-]+))?/)
+    if (!match) return { parts: [0, 0, 0], pre: [] as string[] }
+    return {
+      parts: [parseInt(match[1]), parseInt(match[2]), parseInt(match[3])],
+      pre: match[4] ? match[4].split('.') : []
+    }
   }
 
-  const [aMajor, aMinor, aPatch] = parseVersion(a)
-  const [bMajor, bMinor, bPatch] = parseVersion(b)
+  const va = parseVersion(a)
+  const vb = parseVersion(b)
+  const [aMajor, aMinor, aPatch] = va.parts
+  const [bMajor, bMinor, bPatch] = vb.parts
 
   if (aMajor !== bMajor) return bMajor - aMajor
   if (aMinor !== bMinor) return bMinor - aMinor
-  return bPatch - aPatch
+  if (aPatch !== bPatch) return bPatch - aPatch
+  return comparePreRelease(vb.pre, va.pre)
+}
+
+/**
+ * 比较预发布标识，正式版本（无标识）高于预发布版本，数字标识低于字母标识
+ */
+function comparePreRelease(a: string[], b: string[]): number {
+  if (a.length === 0 || b.length === 0) return b.length - a.length
+  for (let i = 0; i < Math.min(a.length, b.length); i++) {
+    const aNum = /^\d+$/.test(a[i])
+    const bNum = /^\d+$/.test(b[i])
+    if (aNum && bNum) {
+      const diff = parseInt(a[i]) - parseInt(b[i])
+      if (diff !== 0) return diff
+    } else if (aNum !== bNum) {
+      return aNum ? -1 : 1
+    } else if (a[i] !== b[i]) {
+      return a[i] < b[i] ? -1 : 1
+    }
+  }
+  return a.length - b.length
 }
 
 /**